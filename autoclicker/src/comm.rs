@@ -3,7 +3,7 @@ use gtk::{ApplicationWindow, glib};
 use gtk4 as gtk;
 
 use crate::unix;
-use crate::window::{ClickType, Config, MouseButton, Screen, dialogs, settings};
+use crate::window::{Config, MouseButton, Screen, ScrollDirection, dialogs, settings};
 use common::prelude::*;
 
 #[cfg(feature = "dbus")]
@@ -14,11 +14,12 @@ mod socket;
 
 pub(super) trait Method {
 	fn status(window: &ApplicationWindow) -> anyhow::Result<bool>;
-	fn send_message(msg: &Message) -> anyhow::Result<()>;
+	fn send_message(msg: &Message) -> anyhow::Result<Message>;
+	async fn subscribe(sender: async_channel::Sender<Message>) -> anyhow::Result<()>;
 }
 
 pub fn stop(window: &ApplicationWindow) -> anyhow::Result<()> {
-	if let Err(e) = send_message(&Message::StopClicking(StopClicking {})) {
+	if let Err(e) = send_message(&Message::StopClicking(StopClicking::default())) {
 		glib::MainContext::default().spawn_local(dialogs::error_dialog(window.clone(), "Error: comm::stop", e.to_string()));
 		return Err(anyhow::anyhow!(e));
 	}
@@ -26,28 +27,32 @@ pub fn stop(window: &ApplicationWindow) -> anyhow::Result<()> {
 	return Ok(());
 }
 
-pub fn start(window: &ApplicationWindow, config: &Config) -> anyhow::Result<()> {
-	if !is_ready_to_start(window) {
-		return Err(anyhow::anyhow!("daemon not ready"));
+pub fn mouse_button_name(button: MouseButton) -> String {
+	return match button {
+		MouseButton::Left => "left",
+		MouseButton::Right => "right",
+		MouseButton::Middle => "middle",
+		MouseButton::Side => "side",
+		MouseButton::Extra => "extra",
+		MouseButton::Forward => "forward",
+		MouseButton::Back => "back",
+		MouseButton::Task => "task",
 	}
+	.to_string();
+}
 
-	let message = match config.screen {
+/// Job of the current page, as it is sent when pressing start
+pub fn job(config: &Config) -> Job {
+	return match config.screen {
 		Screen::Mouse => {
 			let config = &config.mouse;
-			Message::RepeatingMouseClick(RepeatingMouseClick {
-				button: match config.mouse_button {
-					MouseButton::Left => "left",
-					MouseButton::Right => "right",
-					MouseButton::Middle => "middle",
-				}
-				.to_string(),
-				typ: match config.typ {
-					ClickType::Single => "single",
-					ClickType::Double => "double",
-				}
-				.to_string(),
+			Job::RepeatingMouseClick(RepeatingMouseClick {
+				button: mouse_button_name(config.mouse_button),
+				clicks: config.clicks,
 				amount: config.repeat,
 				interval: config.interval,
+				hold_duration: config.hold_duration,
+				click_spacing: config.click_spacing,
 				position: (
 					if config.enabled_axis.0 {
 						Some(config.position.0)
@@ -60,6 +65,19 @@ pub fn start(window: &ApplicationWindow, config: &Config) -> anyhow::Result<()>
 						None
 					},
 				),
+				interval_random: config.interval_random.clone(),
+				position_random: config.position_random.clone(),
+				route: config
+					.route
+					.iter()
+					.map(|p| RoutePoint {
+						position: p.position,
+						button: mouse_button_name(p.mouse_button),
+						clicks: p.clicks,
+						delay: p.delay,
+					})
+					.collect(),
+				movement: config.movement.clone(),
 			})
 		}
 		Screen::Keyboard => {
@@ -69,27 +87,65 @@ pub fn start(window: &ApplicationWindow, config: &Config) -> anyhow::Result<()>
 				seq.extend([Actions::Press("KEY_ENTER".into()), Actions::Release("KEY_ENTER".into())]);
 			}
 
-			Message::RepeatingKeyboardClick(RepeatingKeyboardClick {
+			Job::RepeatingKeyboardClick(RepeatingKeyboardClick {
 				buttons: seq,
 				amount: config.repeat,
 				interval: config.interval,
 				delay_before_repeat: config.delay_before_repeat,
 				hold_duration: config.hold_duration,
+				interval_random: config.interval_random.clone(),
+			})
+		}
+		Screen::Scroll => {
+			let config = &config.scroll;
+			let distance = config.distance as i32;
+			let (axis, distance) = match config.direction {
+				ScrollDirection::Up => ("vertical", distance),
+				ScrollDirection::Down => ("vertical", -distance),
+				ScrollDirection::Left => ("horizontal", -distance),
+				ScrollDirection::Right => ("horizontal", distance),
+			};
+
+			Job::MouseScroll(MouseScroll {
+				axis: axis.to_string(),
+				distance,
+				hi_res: config.hi_res,
+				amount: config.repeat,
+				interval: config.interval,
+				interval_random: None,
 			})
 		}
 	};
+}
 
-	if let Err(e) = send_message(&message) {
-		glib::MainContext::default().spawn_local(dialogs::error_dialog(window.clone(), "Error: comm::send_message", e.to_string()));
-		return Err(anyhow!(e));
+/// Starts the job of the current page and returns its id, if the daemon reported one
+pub fn start(window: &ApplicationWindow, config: &Config) -> anyhow::Result<Option<u64>> {
+	if !is_ready_to_start(window) {
+		return Err(anyhow::anyhow!("daemon not ready"));
 	}
-	return Ok(());
+
+	return match send_message(&job(config).into()) {
+		Ok(Message::JobQueued(queued)) => Ok(Some(queued.id)),
+		Ok(_) => Ok(None),
+		Err(e) => {
+			glib::MainContext::default().spawn_local(dialogs::error_dialog(window.clone(), "Error: comm::send_message", e.to_string()));
+			Err(anyhow!(e))
+		}
+	};
+}
+
+/// Whether the daemon is running any job
+pub fn is_running() -> anyhow::Result<bool> {
+	return match send_message(&Message::GetStatus(GetStatus {}))? {
+		Message::Status(status) => Ok(status.running),
+		_ => Err(anyhow!("unexpected response from daemon")),
+	};
 }
 
-fn send_message(msg: &Message) -> anyhow::Result<()> {
+fn send_message(msg: &Message) -> anyhow::Result<Message> {
 	if settings().lock().unwrap().general.communication_method == common::settings::latest::Methods::DBus {
 		#[cfg(feature = "dbus")]
-		dbus::DBus::send_message(msg)?;
+		return dbus::DBus::send_message(msg);
 
 		#[cfg(not(feature = "dbus"))]
 		{
@@ -98,7 +154,7 @@ fn send_message(msg: &Message) -> anyhow::Result<()> {
 		}
 	} else {
 		#[cfg(feature = "socket")]
-		socket::UnixSocket::send_message(msg)?;
+		return socket::UnixSocket::send_message(msg);
 
 		#[cfg(not(feature = "socket"))]
 		{
@@ -106,6 +162,28 @@ fn send_message(msg: &Message) -> anyhow::Result<()> {
 			return Err(anyhow!("this build was not compiled with unix socket support"));
 		}
 	}
+}
+
+pub async fn subscribe(sender: async_channel::Sender<Message>) -> anyhow::Result<()> {
+	if settings().lock().unwrap().general.communication_method == common::settings::latest::Methods::DBus {
+		#[cfg(feature = "dbus")]
+		dbus::DBus::subscribe(sender).await?;
+
+		#[cfg(not(feature = "dbus"))]
+		{
+			drop(sender);
+			return Err(anyhow!("this build was not compiled with dbus support"));
+		}
+	} else {
+		#[cfg(feature = "socket")]
+		socket::UnixSocket::subscribe(sender).await?;
+
+		#[cfg(not(feature = "socket"))]
+		{
+			drop(sender);
+			return Err(anyhow!("this build was not compiled with unix socket support"));
+		}
+	}
 
 	#[allow(unreachable_code)]
 	return Ok(());
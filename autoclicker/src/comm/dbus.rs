@@ -1,4 +1,5 @@
 use anyhow::Context;
+use futures_util::StreamExt;
 use gtk::{ApplicationWindow, glib};
 use gtk4 as gtk;
 use tokio::sync::OnceCell;
@@ -10,6 +11,12 @@ use common::prelude::*;
 #[proxy(interface = "dev.land.Autoclicker1", default_service = "dev.land.Autoclicker", default_path = "/dev/land/Autoclicker")]
 trait Daemon {
 	fn request(&self, msg: &str) -> zbus::Result<String>;
+
+	#[zbus(signal)]
+	fn job_finished(&self, id: u64, job: &str, clicked: u64) -> zbus::Result<()>;
+
+	#[zbus(signal)]
+	fn job_failed(&self, id: u64, job: &str, msg: &str) -> zbus::Result<()>;
 }
 
 async fn proxy() -> anyhow::Result<&'static DaemonProxy<'static>> {
@@ -28,7 +35,7 @@ async fn proxy() -> anyhow::Result<&'static DaemonProxy<'static>> {
 
 pub(super) struct DBus;
 impl super::Method for DBus {
-	fn send_message(msg: &Message) -> anyhow::Result<()> {
+	fn send_message(msg: &Message) -> anyhow::Result<Message> {
 		return crate::window::runtime().block_on(async {
 			let proxy = proxy().await?;
 
@@ -45,10 +52,51 @@ impl super::Method for DBus {
 				return Err(anyhow::anyhow!(e.msg));
 			}
 
-			return Ok(());
+			return Ok(response);
 		});
 	}
 
+	async fn subscribe(sender: async_channel::Sender<Message>) -> anyhow::Result<()> {
+		let proxy = proxy().await?;
+		let finished = proxy
+			.receive_job_finished()
+			.await
+			.context("could not subscribe to JobFinished")?
+			.filter_map(|signal| {
+				let event = signal.args().ok().map(|args| {
+					Message::JobFinished(common::JobFinished {
+						id: args.id,
+						job: args.job.to_string(),
+						clicked: args.clicked,
+					})
+				});
+				std::future::ready(event)
+			});
+		let failed = proxy
+			.receive_job_failed()
+			.await
+			.context("could not subscribe to JobFailed")?
+			.filter_map(|signal| {
+				let event = signal.args().ok().map(|args| {
+					Message::JobFailed(common::JobFailed {
+						id: args.id,
+						job: args.job.to_string(),
+						msg: args.msg.to_string(),
+					})
+				});
+				std::future::ready(event)
+			});
+
+		let mut events = futures_util::stream::select(finished, failed);
+		while let Some(event) = events.next().await {
+			if sender.send(event).await.is_err() {
+				break;
+			}
+		}
+
+		return Ok(());
+	}
+
 	fn status(window: &ApplicationWindow) -> anyhow::Result<bool> {
 		return runtime().block_on(async {
 			let connection = zbus::Connection::session()
@@ -5,6 +5,7 @@ use std::{
 	io::{Read, Write},
 	os::unix::net::UnixStream,
 };
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tracing::error;
 
 use crate::window::{dialogs, settings};
@@ -27,7 +28,7 @@ fn socket_file() -> String {
 
 pub(super) struct UnixSocket;
 impl super::Method for UnixSocket {
-	fn send_message(msg: &Message) -> anyhow::Result<()> {
+	fn send_message(msg: &Message) -> anyhow::Result<Message> {
 		let mut stream = UnixStream::connect(socket_file()).context("could not connect to socket")?;
 		let json = Message::encode(msg).context("could not encode as json")?;
 		stream
@@ -47,6 +48,36 @@ impl super::Method for UnixSocket {
 			return Err(anyhow::anyhow!(e.msg));
 		}
 
+		return Ok(response);
+	}
+
+	async fn subscribe(sender: async_channel::Sender<Message>) -> anyhow::Result<()> {
+		if settings().lock().unwrap().general.socket_path.is_none() {
+			return Err(anyhow::anyhow!("socket_path must be supplied when communication_method = UnixSocket"));
+		}
+
+		let mut stream = tokio::net::UnixStream::connect(socket_file())
+			.await
+			.context("could not connect to socket")?;
+		let mut json = Message::encode(&Message::Subscribe(Subscribe {})).context("could not encode as json")?;
+		json.push('\n');
+		stream
+			.write_all(json.as_bytes())
+			.await
+			.context("could not write to socket")?;
+
+		let mut lines = BufReader::new(stream).lines();
+		while let Some(line) = lines
+			.next_line()
+			.await
+			.context("could not read from socket")?
+		{
+			let event = Message::decode(line).context("could not decode json")?;
+			if sender.send(event).await.is_err() {
+				break;
+			}
+		}
+
 		return Ok(());
 	}
 
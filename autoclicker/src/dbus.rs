@@ -38,7 +38,7 @@ async fn send(msg: &str) -> anyhow::Result<String> {
 }
 
 pub async fn send_stop() -> anyhow::Result<()> {
-	let request = Message::StopClicking(StopClicking {});
+	let request = Message::StopClicking(StopClicking::default());
 	let json = Message::encode(&request).context("could not encode as json")?;
 	let response = Message::decode(send(&json).await?).context("could not decode json")?;
 
@@ -6,7 +6,6 @@ use window::*;
 
 mod comm;
 mod key_parser;
-mod keycodes;
 mod shortcuts;
 mod unix;
 
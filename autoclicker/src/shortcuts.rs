@@ -1,6 +1,6 @@
 #![allow(non_upper_case_globals)]
 
-use std::sync::OnceLock;
+use std::sync::{Mutex, OnceLock};
 
 use anyhow::{Context, anyhow};
 use ashpd::{
@@ -15,21 +15,73 @@ use ashpd::{
 use futures_util::{StreamExt, stream};
 use gtk::prelude::*;
 use gtk4 as gtk;
+use serde::{Deserialize, Serialize};
 
 static global_shortcuts: OnceLock<GlobalShortcuts<'_>> = OnceLock::new();
-static global_session: OnceLock<Session<GlobalShortcuts<'_>>> = OnceLock::new();
+static global_session: Mutex<Option<Session<GlobalShortcuts<'_>>>> = Mutex::new(None);
+
+/// What a global shortcut does when it is pressed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ShortcutAction {
+	/// Starts or stops the job of the visible page
+	Toggle,
+	/// Stops every job, whatever started it
+	Stop,
+	StartMouse,
+	StartKeyboard,
+	/// Starts or stops the job of a profile, without switching to it
+	Profile(String),
+}
+
+impl ShortcutAction {
+	/// Id the shortcut is bound with, and reported back with when activated
+	pub fn id(&self) -> String {
+		return match self {
+			Self::Toggle => "toggle-clicking".into(),
+			Self::Stop => "stop-clicking".into(),
+			Self::StartMouse => "start-mouse".into(),
+			Self::StartKeyboard => "start-keyboard".into(),
+			Self::Profile(name) => format!("profile-{name}"),
+		};
+	}
+
+	pub fn description(&self) -> String {
+		return match self {
+			Self::Toggle => "Toggle clicking".into(),
+			Self::Stop => "Stop every job".into(),
+			Self::StartMouse => "Start mouse job".into(),
+			Self::StartKeyboard => "Start keyboard job".into(),
+			Self::Profile(name) => format!("Toggle profile \"{name}\""),
+		};
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Shortcut {
+	pub action: ShortcutAction,
+	/// Trigger suggested to the desktop, which lets the user pick the actual one
+	pub trigger: String,
+}
+
+pub fn default_shortcuts() -> Vec<Shortcut> {
+	return vec![Shortcut {
+		action: ShortcutAction::Toggle,
+		trigger: "F6".into(),
+	}];
+}
 
 pub async fn stop_session() -> anyhow::Result<()> {
-	global_session
-		.get()
-		.context("session not inited")?
-		.close()
-		.await
-		.context("could not close session")?;
+	let session = global_session
+		.lock()
+		.unwrap()
+		.take()
+		.context("session not inited")?;
+	session.close().await.context("could not close session")?;
 	return Ok(());
 }
 
-pub async fn listen_events<F: Fn()>(func: F) -> anyhow::Result<()> {
+/// Calls `func` with the id of every activated shortcut
+pub async fn listen_events<F: Fn(&str)>(func: F) -> anyhow::Result<()> {
 	let shortcuts = global_shortcuts.get().context("session not inited")?;
 	let Ok(activated_stream) = shortcuts.receive_activated().await else {
 		return Err(anyhow!("could not receive activated shortcuts"));
@@ -45,27 +97,41 @@ pub async fn listen_events<F: Fn()>(func: F) -> anyhow::Result<()> {
 	while let Some(event) = events.next().await {
 		let Event::Activated(activation) = event;
 		tracing::debug!(?activation);
-		func();
+		func(activation.shortcut_id());
 	}
 
 	return Ok(());
 }
 
-pub async fn start_session<W: IsA<gtk::Widget>>(widget: &W) -> anyhow::Result<()> {
+/// Binds the shortcuts in a new session, replacing the previous one since a session can only bind once
+pub async fn start_session<W: IsA<gtk::Widget>>(widget: &W, shortcuts: &[Shortcut]) -> anyhow::Result<()> {
 	let root = widget.native().unwrap();
 	let ident = WindowIdentifier::from_native(&root).await;
-	let shortcut = NewShortcut::new("toggle-clicking", "Toggle clicking").preferred_trigger("F6");
+	let new_shortcuts: Vec<NewShortcut> = shortcuts
+		.iter()
+		.map(|s| NewShortcut::new(s.action.id(), s.action.description()).preferred_trigger(Some(s.trigger.as_str()).filter(|t| !t.is_empty())))
+		.collect();
 
-	let shortcuts = GlobalShortcuts::new()
-		.await
-		.context("could not get GlobalShortcuts portal")?;
-	let session = shortcuts
+	let portal = match global_shortcuts.get() {
+		Some(o) => o,
+		None => {
+			let portal = GlobalShortcuts::new()
+				.await
+				.context("could not get GlobalShortcuts portal")?;
+			global_shortcuts.get_or_init(|| portal)
+		}
+	};
+
+	if global_session.lock().unwrap().is_some() {
+		stop_session().await?;
+	}
+
+	let session = portal
 		.create_session()
 		.await
 		.context("could not create GlobalShortcuts session")?;
-	let shortcuts_vec = [shortcut];
-	let request = shortcuts
-		.bind_shortcuts(&session, &shortcuts_vec, ident.as_ref())
+	let request = portal
+		.bind_shortcuts(&session, &new_shortcuts, ident.as_ref())
 		.await
 		.context("could not bind shortcuts")?;
 	let response = request.response();
@@ -75,8 +141,7 @@ pub async fn start_session<W: IsA<gtk::Widget>>(widget: &W) -> anyhow::Result<()
 			other => anyhow!("{}", other),
 		});
 	}
-	global_shortcuts.set(shortcuts).unwrap();
-	global_session.set(session).unwrap();
+	*global_session.lock().unwrap() = Some(session);
 
 	return Ok(());
 }
@@ -1,8 +1,7 @@
 use gtk::prelude::*;
-use gtk::{Application, ApplicationWindow, Stack, StackSwitcher, StackTransitionType};
+use gtk::{Application, ApplicationWindow, Stack, StackSwitcher, StackTransitionType, glib};
 use gtk4 as gtk;
 
-use anyhow::Context;
 use common::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::sync::OnceLock;
@@ -11,6 +10,8 @@ use tokio::runtime::Runtime;
 
 pub mod dialogs;
 mod events;
+mod macros;
+mod profiles;
 mod widgets;
 
 pub(crate) fn runtime() -> &'static Runtime {
@@ -35,11 +36,16 @@ pub(crate) fn settings() -> Arc<Mutex<settings::Settings>> {
 	return SETTINGS.get().unwrap().clone();
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub(super) enum MouseButton {
 	Left,
 	Right,
 	Middle,
+	Side,
+	Extra,
+	Forward,
+	Back,
+	Task,
 }
 
 impl Default for MouseButton {
@@ -52,6 +58,7 @@ impl Default for MouseButton {
 pub(super) enum Screen {
 	Mouse,
 	Keyboard,
+	Scroll,
 }
 
 impl Default for Screen {
@@ -67,47 +74,90 @@ impl std::str::FromStr for Screen {
 		return Ok(match s {
 			"mouse" => Self::Mouse,
 			"keyboard" => Self::Keyboard,
+			"scroll" => Self::Scroll,
 			_ => return Err(()),
 		});
 	}
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub(super) enum ClickType {
-	Single,
-	Double,
+fn default_clicks() -> u32 {
+	return 1;
 }
 
-impl Default for ClickType {
-	fn default() -> Self {
-		return Self::Single;
+fn default_click_spacing() -> u64 {
+	return 50;
+}
+
+/// Accepts the click type that was saved as `typ` before the click count existed
+fn deserialize_clicks<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+	#[derive(Deserialize)]
+	#[serde(untagged)]
+	enum Clicks {
+		Count(u32),
+		Type(String),
 	}
+
+	return match Clicks::deserialize(deserializer)? {
+		Clicks::Count(clicks) => Ok(clicks),
+		Clicks::Type(typ) => match typ.as_str() {
+			"Single" => Ok(1),
+			"Double" => Ok(2),
+			_ => Err(serde::de::Error::custom(format!("unknown click type {typ}"))),
+		},
+	};
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct RoutePoint {
+	pub position: (i32, i32),
+	pub mouse_button: MouseButton,
+	pub clicks: u32,
+	pub delay: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(super) struct MouseConfig {
 	pub mouse_button: MouseButton,
-	pub typ: ClickType,
+	#[serde(default = "default_clicks", alias = "typ", deserialize_with = "deserialize_clicks")]
+	pub clicks: u32,
 	pub repeat: u64,
 	pub position: (i32, i32),
 	pub enabled_axis: (bool, bool),
 	pub interval: u64,
+	#[serde(default)]
+	pub hold_duration: u64,
+	#[serde(default = "default_click_spacing", alias = "double_click_gap")]
+	pub click_spacing: u64,
+	#[serde(default)]
+	pub interval_random: Option<Randomization>,
+	#[serde(default)]
+	pub position_random: Option<Randomization>,
+	#[serde(default)]
+	pub route: Vec<RoutePoint>,
+	#[serde(default)]
+	pub movement: Option<Movement>,
 }
 
 impl Default for MouseConfig {
 	fn default() -> Self {
 		return Self {
 			mouse_button: MouseButton::default(),
-			typ: ClickType::default(),
+			clicks: default_clicks(),
 			repeat: 0,
 			position: (0, 0),
 			enabled_axis: (false, false),
 			interval: 25,
+			hold_duration: 0,
+			click_spacing: default_click_spacing(),
+			interval_random: None,
+			position_random: None,
+			route: Vec::new(),
+			movement: None,
 		};
 	}
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(super) struct KeyboardConfig {
 	pub sequence: Vec<Actions>,
 	pub raw_sequence: String,
@@ -116,6 +166,8 @@ pub(super) struct KeyboardConfig {
 	pub interval: u64,
 	pub delay_before_repeat: u64,
 	pub hold_duration: u64,
+	#[serde(default)]
+	pub interval_random: Option<Randomization>,
 }
 
 impl Default for KeyboardConfig {
@@ -128,15 +180,116 @@ impl Default for KeyboardConfig {
 			interval: 25,
 			delay_before_repeat: 0,
 			hold_duration: 0,
+			interval_random: None,
+		};
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) enum ScrollDirection {
+	Up,
+	Down,
+	Left,
+	Right,
+}
+
+impl Default for ScrollDirection {
+	fn default() -> Self {
+		return Self::Down;
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct ScrollConfig {
+	pub direction: ScrollDirection,
+	pub distance: u32,
+	pub hi_res: bool,
+	pub repeat: u64,
+	pub interval: u64,
+}
+
+impl Default for ScrollConfig {
+	fn default() -> Self {
+		return Self {
+			direction: ScrollDirection::default(),
+			distance: 1,
+			hi_res: false,
+			repeat: 0,
+			interval: 100,
 		};
 	}
 }
 
-#[derive(Default, Debug, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub(super) struct Config {
 	pub screen: Screen,
 	pub mouse: MouseConfig,
 	pub keyboard: KeyboardConfig,
+	#[serde(default)]
+	pub scroll: ScrollConfig,
+}
+
+/// Adds the mouse, keyboard and scroll pages, showing the one of the saved screen
+pub(super) fn build_pages(window: &ApplicationWindow, stack: &Stack, config: Arc<Mutex<Config>>) {
+	// adding the first page makes it visible, which overwrites the screen
+	let screen = config.lock().unwrap().screen.clone();
+
+	{
+		let container = gtk::Box::builder()
+			.orientation(gtk::Orientation::Vertical)
+			.spacing(12)
+			.build();
+
+		container.append(&widgets::click_type(window, config.clone()));
+		container.append(&widgets::click_repeat(window, config.clone()));
+		container.append(&widgets::click_position(window, config.clone()));
+		container.append(&widgets::click_route(window, config.clone()));
+
+		stack.add_titled(&container, Some("mouse"), "Mouse");
+	}
+
+	{
+		let container = gtk::Box::builder()
+			.orientation(gtk::Orientation::Vertical)
+			.spacing(12)
+			.build();
+
+		container.append(&widgets::key_sequence(window, config.clone()));
+		container.append(&widgets::click_repeat_keyboard(window, config.clone()));
+		container.append(&widgets::click_interval_keyboard(window, config.clone()));
+
+		stack.add_titled(&container, Some("keyboard"), "Keyboard");
+	}
+
+	{
+		let container = gtk::Box::builder()
+			.orientation(gtk::Orientation::Vertical)
+			.spacing(12)
+			.build();
+
+		container.append(&widgets::scroll_direction(window, config.clone()));
+		container.append(&widgets::scroll_repeat(window, config.clone()));
+
+		stack.add_titled(&container, Some("scroll"), "Scroll");
+	}
+
+	match screen {
+		Screen::Mouse => {}
+		Screen::Keyboard => stack.set_visible_child_name("keyboard"),
+		Screen::Scroll => stack.set_visible_child_name("scroll"),
+	}
+}
+
+/// Rebuilds the pages after the config was replaced, e.g. by importing a macro or switching profiles
+pub(super) fn rebuild_pages(window: &ApplicationWindow, stack: &Stack, config: Arc<Mutex<Config>>) {
+	// removing the pages changes the visible one, which overwrites the screen
+	let screen = config.lock().unwrap().screen.clone();
+	while let Some(page) = stack.first_child() {
+		stack.remove(&page);
+	}
+
+	config.lock().unwrap().screen = screen;
+	build_pages(window, stack, config);
 }
 
 pub struct Window {
@@ -178,10 +331,32 @@ impl Window {
 		self.app.run();
 	}
 
-	fn build_titlebar(switcher: &gtk::StackSwitcher) -> libadwaita::HeaderBar {
+	fn build_titlebar(switcher: &gtk::StackSwitcher, profiles: &gtk::DropDown) -> libadwaita::HeaderBar {
 		let header = libadwaita::HeaderBar::new();
 
 		header.set_title_widget(Some(switcher));
+		header.pack_start(profiles);
+
+		let profile_menu = gtk::gio::Menu::new();
+		profile_menu.append(Some("New profile…"), Some("win.new-profile"));
+		profile_menu.append(Some("Duplicate profile…"), Some("win.duplicate-profile"));
+		profile_menu.append(Some("Rename profile…"), Some("win.rename-profile"));
+		profile_menu.append(Some("Delete profile"), Some("win.delete-profile"));
+
+		let macro_menu = gtk::gio::Menu::new();
+		macro_menu.append(Some("Import macro…"), Some("win.import-macro"));
+		macro_menu.append(Some("Export macro…"), Some("win.export-macro"));
+
+		let menu = gtk::gio::Menu::new();
+		menu.append_section(None, &profile_menu);
+		menu.append_section(None, &macro_menu);
+		menu.append(Some("Global shortcuts…"), Some("win.edit-shortcuts"));
+		let button = gtk::MenuButton::builder()
+			.icon_name("open-menu-symbolic")
+			.menu_model(&menu)
+			.build();
+		header.pack_end(&button);
+
 		if settings().lock().unwrap().client.disable_window_controls {
 			header.set_show_end_title_buttons(false);
 		}
@@ -223,62 +398,56 @@ scrolledwindow > textview.monospace {
 			.spacing(24)
 			.build();
 
-		let mut res = confy::load(class.as_str(), Some("app-data"));
-		if res.is_err() {
-			let path = confy::get_configuration_file_path(class.as_str(), Some("app-data")).context("could not get config file path")?;
-			std::fs::remove_file(path).context("could not delete app-data file")?;
-			tracing::info!("deleted outdated app-data file");
-			res = confy::load(class.as_str(), Some("app-data"));
+		// before the pages are built, they parse the saved key sequences
+		let layout = settings().lock().unwrap().general.keyboard_layout.clone();
+		match common::xkb::Layout::load(&layout) {
+			Ok(layout) => common::key_parser::set_layout(layout),
+			Err(e) => {
+				tracing::error!("could not load keyboard layout: {e:#}");
+				glib::spawn_future_local(dialogs::error_dialog(
+					window.clone(),
+					"Could not load keyboard layout",
+					format!("{e:#}\n\nStrings are typed with the US layout until the keyboard_layout settings are fixed."),
+				));
+			}
 		}
 
-		let config: Arc<Mutex<Config>> = Arc::new(Mutex::new(res.context("could not load app-data")?));
+		let data = profiles::load(&window, class.as_str())?;
+		let config: Arc<Mutex<Config>> = Arc::new(Mutex::new(data.profiles[&data.profile].clone()));
+		let data = Arc::new(Mutex::new(data));
 
 		let stack = Stack::builder()
 			.transition_type(StackTransitionType::SlideLeftRight)
 			.build();
 		let switcher = StackSwitcher::builder().stack(&stack).build();
-		window.set_titlebar(Some(&Window::build_titlebar(&switcher)));
+		let profiles = profiles::Profiles::new(&window, &stack, config.clone(), data.clone(), class);
+		window.set_titlebar(Some(&Window::build_titlebar(&switcher, &profiles.dropdown)));
 		container.append(&stack);
 
-		{
-			let container = gtk::Box::builder()
-				.orientation(gtk::Orientation::Vertical)
-				.spacing(12)
-				.build();
-
-			container.append(&widgets::click_type(config.clone()));
-			container.append(&widgets::click_repeat(&window, config.clone()));
-			container.append(&widgets::click_position(&window, config.clone()));
-
-			stack.add_titled(&container, Some("mouse"), "Mouse");
-		}
-
-		{
-			let container = gtk::Box::builder()
-				.orientation(gtk::Orientation::Vertical)
-				.spacing(12)
-				.build();
-
-			container.append(&widgets::key_sequence(&window, config.clone()));
-			container.append(&widgets::click_repeat_keyboard(&window, config.clone()));
-			container.append(&widgets::click_interval_keyboard(&window, config.clone()));
-
-			stack.add_titled(&container, Some("keyboard"), "Keyboard");
-		}
-
-		if config.lock().unwrap().screen == Screen::Keyboard {
-			stack.set_visible_child_name("keyboard");
-		}
+		build_pages(&window, &stack, config.clone());
+		macros::add_actions(&window, &stack, config.clone());
 
 		let clone = config.clone();
 		stack.connect_notify_local(Some("visible-child-name"), move |stack, _| {
+			// unset while the pages are rebuilt
+			let Some(screen) = stack.visible_child_name() else {
+				return;
+			};
 			let mut config = clone.lock().unwrap();
-			let screen = stack.visible_child_name().unwrap();
 			config.screen = screen.parse().unwrap();
 			tracing::trace!("current page: {}", screen);
 		});
 
-		container.append(&widgets::start_clicking(&window, config));
+		let action = gtk::gio::SimpleAction::new("edit-shortcuts", None);
+		let clone = data.clone();
+		action.connect_activate(glib::clone!(
+			#[weak]
+			window,
+			move |_, _| dialogs::shortcuts_dialog(&window, clone.clone())
+		));
+		window.add_action(&action);
+
+		container.append(&widgets::start_clicking(&window, config, data));
 		window.set_child(Some(&container));
 
 		window.present();
@@ -7,7 +7,8 @@ use gtk::{
 use gtk4 as gtk;
 use std::sync::{Arc, Mutex};
 
-use super::{Config, runtime};
+use super::{Config, profiles::AppData, runtime, settings};
+use crate::shortcuts::{Shortcut, ShortcutAction};
 use crate::{key_parser, unix};
 
 pub async fn critical_dialog(window: ApplicationWindow, title: &str, msg: String) {
@@ -33,6 +34,51 @@ pub async fn error_dialog(window: ApplicationWindow, title: &str, msg: String) {
 	info_dialog.show(Some(&window));
 }
 
+/// Asks for the value of every parameter of a key sequence, `None` if cancelled
+pub async fn parameters_dialog(window: &ApplicationWindow, parameters: &[String]) -> Option<key_parser::Parameters> {
+	use libadwaita::prelude::*;
+
+	let grid = gtk::Grid::builder()
+		.row_spacing(6)
+		.column_spacing(12)
+		.build();
+
+	let entries: Vec<(String, gtk::Entry)> = parameters
+		.iter()
+		.enumerate()
+		.map(|(i, name)| {
+			let label = gtk::Label::new(Some(name));
+			label.set_halign(gtk::Align::End);
+			grid.attach(&label, 0, i as i32, 1, 1);
+
+			let entry = gtk::Entry::builder()
+				.hexpand(true)
+				.activates_default(true)
+				.build();
+			grid.attach(&entry, 1, i as i32, 1, 1);
+			return (name.clone(), entry);
+		})
+		.collect();
+
+	let dialog = libadwaita::AlertDialog::new(Some("Sequence parameters"), Some("Values filled into the variables of the key sequence"));
+	dialog.add_responses(&[("cancel", "Cancel"), ("start", "Start")]);
+	dialog.set_response_appearance("start", libadwaita::ResponseAppearance::Suggested);
+	dialog.set_default_response(Some("start"));
+	dialog.set_close_response("cancel");
+	dialog.set_extra_child(Some(&grid));
+
+	if dialog.choose_future(window).await != "start" {
+		return None;
+	}
+
+	return Some(
+		entries
+			.into_iter()
+			.map(|(name, entry)| (name, entry.text().to_string()))
+			.collect(),
+	);
+}
+
 pub async fn enable_service_dialog(window: ApplicationWindow) {
 	let question_dialog = gtk::AlertDialog::builder()
 		.modal(true)
@@ -319,6 +365,187 @@ pub fn sequence_dialog(window: &ApplicationWindow, config: Arc<Mutex<Config>>) {
 	dialog.present();
 }
 
+fn shortcut_rows(list: Arc<Mutex<Vec<Shortcut>>>, available: Arc<Mutex<Vec<ShortcutAction>>>, profiles: &[String], rows: &gtk::Box, add: &gtk::DropDown) {
+	while let Some(child) = rows.first_child() {
+		rows.remove(&child);
+	}
+
+	let shortcuts = list.lock().unwrap().clone();
+	for (i, shortcut) in shortcuts.iter().enumerate() {
+		let hbox = gtk::Box::builder()
+			.orientation(gtk::Orientation::Horizontal)
+			.spacing(6)
+			.build();
+
+		let label = gtk::Label::new(Some(&shortcut.action.description()));
+		label.set_hexpand(true);
+		label.set_halign(gtk::Align::Start);
+		hbox.append(&label);
+
+		let entry = gtk::Entry::new();
+		entry.set_width_chars(12);
+		entry.set_placeholder_text(Some("Trigger"));
+		entry.set_tooltip_text(Some(
+			"Suggested trigger, e.g. F6 or CTRL+SHIFT+F6. The desktop lets you change it when the shortcuts are saved",
+		));
+		entry.set_text(&shortcut.trigger);
+		let list_clone = list.clone();
+		entry.connect_changed(move |entry| {
+			if let Some(shortcut) = list_clone.lock().unwrap().get_mut(i) {
+				shortcut.trigger = entry.text().trim().to_string();
+			}
+		});
+		hbox.append(&entry);
+
+		let remove_btn = gtk::Button::from_icon_name("list-remove-symbolic");
+		let list_clone = list.clone();
+		let available_clone = available.clone();
+		let profiles = profiles.to_vec();
+		remove_btn.connect_clicked(clone!(
+			#[weak]
+			rows,
+			#[weak]
+			add,
+			move |_| {
+				list_clone.lock().unwrap().remove(i);
+				shortcut_rows(list_clone.clone(), available_clone.clone(), &profiles, &rows, &add);
+			}
+		));
+		hbox.append(&remove_btn);
+
+		rows.append(&hbox);
+	}
+
+	// every action can only be bound once
+	let mut actions = vec![ShortcutAction::Toggle, ShortcutAction::Stop, ShortcutAction::StartMouse, ShortcutAction::StartKeyboard];
+	actions.extend(profiles.iter().cloned().map(ShortcutAction::Profile));
+	actions.retain(|action| !shortcuts.iter().any(|s| s.action == *action));
+
+	let names: Vec<String> = actions.iter().map(ShortcutAction::description).collect();
+	let names: Vec<&str> = names.iter().map(String::as_str).collect();
+	add.set_model(Some(&gtk::StringList::new(&names)));
+	add.set_sensitive(!actions.is_empty());
+	*available.lock().unwrap() = actions;
+}
+
+pub(super) fn shortcuts_dialog(window: &ApplicationWindow, data: Arc<Mutex<AppData>>) {
+	let dialog = gtk::Window::builder()
+		.transient_for(window)
+		.modal(true)
+		.title("Global shortcuts")
+		.default_width(450)
+		.build();
+
+	let vbox = gtk::Box::builder()
+		.orientation(gtk::Orientation::Vertical)
+		.margin_top(24)
+		.margin_bottom(24)
+		.margin_start(24)
+		.margin_end(24)
+		.spacing(12)
+		.build();
+	dialog.set_child(Some(&vbox));
+
+	let (list, profiles) = {
+		let data = data.lock().unwrap();
+		(Arc::new(Mutex::new(data.shortcuts.clone())), data.profiles.keys().cloned().collect::<Vec<String>>())
+	};
+	let available: Arc<Mutex<Vec<ShortcutAction>>> = Arc::new(Mutex::new(Vec::new()));
+
+	let rows = gtk::Box::builder()
+		.orientation(gtk::Orientation::Vertical)
+		.spacing(6)
+		.build();
+	vbox.append(&rows);
+
+	let add_box = gtk::Box::builder()
+		.orientation(gtk::Orientation::Horizontal)
+		.spacing(6)
+		.build();
+	let add_dropdown = gtk::DropDown::builder().hexpand(true).build();
+	add_box.append(&add_dropdown);
+
+	let add_button = gtk::Button::with_label("Add");
+	add_button.connect_clicked(clone!(
+		#[strong]
+		list,
+		#[strong]
+		available,
+		#[strong]
+		profiles,
+		#[weak]
+		rows,
+		#[weak]
+		add_dropdown,
+		move |_| {
+			let Some(action) = available
+				.lock()
+				.unwrap()
+				.get(add_dropdown.selected() as usize)
+				.cloned()
+			else {
+				return;
+			};
+
+			list.lock()
+				.unwrap()
+				.push(Shortcut { action, trigger: String::new() });
+			shortcut_rows(list.clone(), available.clone(), &profiles, &rows, &add_dropdown);
+		}
+	));
+	add_box.append(&add_button);
+	vbox.append(&add_box);
+
+	shortcut_rows(list.clone(), available, &profiles, &rows, &add_dropdown);
+
+	let button_grid = gtk::Grid::builder()
+		.row_spacing(6)
+		.column_spacing(6)
+		.column_homogeneous(true)
+		.row_homogeneous(true)
+		.build();
+
+	let cancel_button = gtk::Button::with_label("Cancel");
+	cancel_button.connect_clicked(clone!(
+		#[weak]
+		dialog,
+		move |_| {
+			dialog.close();
+		}
+	));
+
+	let ok_button = gtk::Button::with_label("Save");
+	ok_button.add_css_class("suggested-action");
+	ok_button.connect_clicked(clone!(
+		#[weak]
+		window,
+		#[weak]
+		dialog,
+		move |_| {
+			let shortcuts = list.lock().unwrap().clone();
+			data.lock().unwrap().shortcuts = shortcuts.clone();
+			dialog.close();
+
+			if settings().lock().unwrap().client.disable_global_shortcut {
+				return;
+			}
+
+			glib::spawn_future_local(async move {
+				if let Err(e) = crate::shortcuts::start_session(&window, &shortcuts).await {
+					tracing::error!("could not bind shortcuts: {e}");
+					error_dialog(window.clone(), "Could not bind shortcuts", e.to_string()).await;
+				}
+			});
+		}
+	));
+
+	button_grid.attach(&cancel_button, 0, 0, 1, 1);
+	button_grid.attach(&ok_button, 1, 0, 1, 1);
+	vbox.append(&button_grid);
+
+	dialog.present();
+}
+
 async fn reboot_dialog(window: &ApplicationWindow) {
 	tracing::debug!("opening reboot dialog");
 	let info_dialog = gtk::AlertDialog::builder()
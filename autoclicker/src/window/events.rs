@@ -1,11 +1,14 @@
 use anyhow::{Context, anyhow};
 use gtk::ApplicationWindow;
+use gtk::glib;
 use gtk::prelude::*;
 use gtk4::{self as gtk, Button};
 
 use std::sync::{Arc, Mutex};
 
-use super::Config;
+use super::{Config, Screen, dialogs, profiles::AppData};
+use crate::key_parser::{self, Parameters};
+use crate::shortcuts::ShortcutAction;
 
 pub async fn get_coords() -> anyhow::Result<(i32, i32)> {
 	let output = tokio::process::Command::new("/usr/bin/slurp")
@@ -30,21 +33,134 @@ pub async fn get_coords() -> anyhow::Result<(i32, i32)> {
 	return Ok(pos);
 }
 
+/// Id of the job this window started, other jobs finishing leave the button alone
+#[allow(non_upper_case_globals)]
+static started_job: Mutex<Option<u64>> = Mutex::new(None);
+
+fn started(window: &ApplicationWindow, button: &Button, config: &Config) {
+	if let Ok(id) = crate::comm::start(window, config) {
+		*started_job.lock().unwrap() = id;
+		button.remove_css_class("suggested-action");
+		button.add_css_class("destructive-action");
+		button.set_label("Stop");
+	}
+}
+
+/// Starts the job of the current page, asking for the parameters of the key sequence first
+fn start(window: &ApplicationWindow, button: &Button, config: &Config) {
+	if config.screen != Screen::Keyboard {
+		started(window, button, config);
+		return;
+	}
+
+	let parameters = key_parser::parameters(&config.keyboard.raw_sequence);
+	let window = window.clone();
+	let button = button.clone();
+	let mut config = config.clone();
+	glib::spawn_future_local(async move {
+		let values = if parameters.is_empty() {
+			Parameters::new()
+		} else {
+			let Some(values) = dialogs::parameters_dialog(&window, &parameters).await else {
+				return;
+			};
+			values
+		};
+
+		// parsed again instead of using the stored actions, the keyboard layout may have changed since it was edited
+		config.keyboard.sequence = match key_parser::parse_with(config.keyboard.raw_sequence.clone(), &values) {
+			Ok(o) => o,
+			Err(e) => {
+				dialogs::error_dialog(window.clone(), "Error: parse_sequence", e.to_string()).await;
+				return;
+			}
+		};
+		started(&window, &button, &config);
+	});
+}
+
+fn stop(window: &ApplicationWindow, button: &Button) {
+	if crate::comm::stop(window).is_ok() {
+		job_ended(button);
+	}
+}
+
 pub fn primary_button(window: &ApplicationWindow, button: &Button, config: Arc<Mutex<Config>>) {
 	let s = button.label().unwrap();
 
-	#[allow(clippy::collapsible_if)]
 	if s == "Start" {
-		if crate::comm::start(window, &config.lock().unwrap()).is_ok() {
-			button.remove_css_class("suggested-action");
-			button.add_css_class("destructive-action");
-			button.set_label("Stop");
-		}
+		start(window, button, &config.lock().unwrap());
 	} else if s == "Stop" {
-		if crate::comm::stop(window).is_ok() {
-			button.remove_css_class("destructive-action");
-			button.add_css_class("suggested-action");
-			button.set_label("Start");
+		stop(window, button);
+	}
+}
+
+/// Runs the action of the activated global shortcut
+pub fn shortcut(window: &ApplicationWindow, button: &Button, config: Arc<Mutex<Config>>, data: Arc<Mutex<AppData>>, id: &str) {
+	let action = data
+		.lock()
+		.unwrap()
+		.shortcuts
+		.iter()
+		.map(|s| s.action.clone())
+		.find(|action| action.id() == id);
+
+	match action {
+		Some(ShortcutAction::Toggle) => primary_button(window, button, config),
+		Some(ShortcutAction::Stop) => stop(window, button),
+		Some(ShortcutAction::StartMouse) | Some(ShortcutAction::StartKeyboard) => {
+			let mut config = config.lock().unwrap().clone();
+			config.screen = if action == Some(ShortcutAction::StartMouse) {
+				Screen::Mouse
+			} else {
+				Screen::Keyboard
+			};
+			start(window, button, &config);
 		}
+		Some(ShortcutAction::Profile(name)) => {
+			if button.label().unwrap() == "Stop" {
+				stop(window, button);
+				return;
+			}
+
+			// the profile shown in the window is newer than its stored copy
+			let config = {
+				let data = data.lock().unwrap();
+				if data.profile == name {
+					Some(config.lock().unwrap().clone())
+				} else {
+					data.profiles.get(&name).cloned()
+				}
+			};
+
+			match config {
+				Some(config) => start(window, button, &config),
+				None => tracing::warn!("shortcut for unknown profile {name}"),
+			}
+		}
+		None => tracing::warn!("unknown shortcut: {id}"),
+	}
+}
+
+/// Resets the button if the job that ended is the one this window started, returns whether it was
+pub fn job_event(button: &Button, id: u64) -> bool {
+	let ours = match *started_job.lock().unwrap() {
+		Some(started) => started == id,
+		// the daemon did not report an id, so only its status tells if anything is still running
+		None => !crate::comm::is_running().unwrap_or(false),
+	};
+	if ours {
+		job_ended(button);
+	}
+
+	return ours;
+}
+
+pub fn job_ended(button: &Button) {
+	*started_job.lock().unwrap() = None;
+	if button.label().unwrap() == "Stop" {
+		button.remove_css_class("destructive-action");
+		button.add_css_class("suggested-action");
+		button.set_label("Start");
 	}
 }
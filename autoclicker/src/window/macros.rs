@@ -0,0 +1,209 @@
+use anyhow::anyhow;
+use gtk::{
+	ApplicationWindow, Stack,
+	glib::{self, clone},
+	prelude::*,
+};
+use gtk4 as gtk;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use super::{Config, KeyboardConfig, MouseButton, MouseConfig, RoutePoint, Screen, ScrollConfig, ScrollDirection, dialogs};
+use crate::{comm, key_parser};
+use common::prelude::*;
+
+fn mouse_button(name: &str) -> anyhow::Result<MouseButton> {
+	return Ok(match name {
+		"left" => MouseButton::Left,
+		"right" => MouseButton::Right,
+		"middle" => MouseButton::Middle,
+		"side" => MouseButton::Side,
+		"extra" => MouseButton::Extra,
+		"forward" => MouseButton::Forward,
+		"back" => MouseButton::Back,
+		"task" => MouseButton::Task,
+		_ => return Err(anyhow!("unknown mouse button: {name}")),
+	});
+}
+
+/// Macro file with the job of the current page
+fn export(config: &Config) -> MacroFile {
+	let mut job = MacroJob::new(comm::job(config));
+	if let Job::RepeatingKeyboardClick(keyboard) = &mut job.job {
+		// saved as typed so it stays readable and can be edited again after importing
+		keyboard.buttons.clear();
+		let mut sequence = config.keyboard.raw_sequence.clone();
+		if config.keyboard.enter_after {
+			sequence.push_str(" Enter");
+		}
+		job.sequence = Some(sequence);
+	}
+
+	return MacroFile::new(vec![job]);
+}
+
+/// Loads the first job of a macro file into its page and returns that page
+fn import(file: &MacroFile, config: &mut Config) -> anyhow::Result<Screen> {
+	let Some(first) = file.jobs.first() else {
+		return Err(anyhow!("the macro file has no jobs"));
+	};
+
+	return Ok(match &first.job {
+		Job::RepeatingMouseClick(job) => {
+			config.mouse = MouseConfig {
+				mouse_button: mouse_button(&job.button)?,
+				clicks: job.clicks,
+				repeat: job.amount,
+				position: (job.position.0.unwrap_or(0), job.position.1.unwrap_or(0)),
+				enabled_axis: (job.position.0.is_some(), job.position.1.is_some()),
+				interval: job.interval,
+				hold_duration: job.hold_duration,
+				click_spacing: job.click_spacing,
+				interval_random: job.interval_random.clone(),
+				position_random: job.position_random.clone(),
+				route: job
+					.route
+					.iter()
+					.map(|p| {
+						return Ok(RoutePoint {
+							position: p.position,
+							mouse_button: mouse_button(&p.button)?,
+							clicks: p.clicks,
+							delay: p.delay,
+						});
+					})
+					.collect::<anyhow::Result<_>>()?,
+				movement: job.movement.clone(),
+			};
+			Screen::Mouse
+		}
+		Job::RepeatingKeyboardClick(job) => {
+			let Some(sequence) = &first.sequence else {
+				return Err(anyhow!("keyboard jobs need a `sequence` to be edited here, run the file with `autoclickerctl run` instead"));
+			};
+
+			config.keyboard = KeyboardConfig {
+				sequence: key_parser::parse(sequence.clone())?,
+				raw_sequence: sequence.clone(),
+				enter_after: false,
+				repeat: job.amount,
+				interval: job.interval,
+				delay_before_repeat: job.delay_before_repeat,
+				hold_duration: job.hold_duration,
+				interval_random: job.interval_random.clone(),
+			};
+			Screen::Keyboard
+		}
+		Job::MouseScroll(job) => {
+			let direction = match (job.axis.as_str(), job.distance < 0) {
+				("vertical", false) => ScrollDirection::Up,
+				("vertical", true) => ScrollDirection::Down,
+				("horizontal", false) => ScrollDirection::Right,
+				("horizontal", true) => ScrollDirection::Left,
+				(axis, _) => return Err(anyhow!("unknown scroll axis: {axis}")),
+			};
+
+			config.scroll = ScrollConfig {
+				direction,
+				distance: job.distance.unsigned_abs(),
+				hi_res: job.hi_res,
+				repeat: job.amount,
+				interval: job.interval,
+			};
+			Screen::Scroll
+		}
+		job => return Err(anyhow!("{} jobs can't be edited here, run the file with `autoclickerctl run` instead", job.kind())),
+	});
+}
+
+fn file_dialog(title: &str) -> gtk::FileDialog {
+	let filter = gtk::FileFilter::new();
+	filter.set_name(Some("Macro files"));
+	filter.add_suffix("json");
+
+	return gtk::FileDialog::builder()
+		.title(title)
+		.modal(true)
+		.default_filter(&filter)
+		.build();
+}
+
+async fn import_file(window: &ApplicationWindow, stack: &Stack, config: Arc<Mutex<Config>>, path: PathBuf) -> anyhow::Result<()> {
+	let file = MacroFile::load(&path)?;
+	let screen = import(&file, &mut config.lock().unwrap())?;
+
+	config.lock().unwrap().screen = screen;
+	super::rebuild_pages(window, stack, config);
+
+	if file.jobs.len() > 1 {
+		dialogs::error_dialog(
+			window.clone(),
+			"Macro imported",
+			format!(
+				"Only the first of {} jobs was imported, run the file with `autoclickerctl run` to use all of them.",
+				file.jobs.len()
+			),
+		)
+		.await;
+	}
+
+	return Ok(());
+}
+
+/// Adds the `win.import-macro` and `win.export-macro` actions
+pub fn add_actions(window: &ApplicationWindow, stack: &Stack, config: Arc<Mutex<Config>>) {
+	let action = gtk::gio::SimpleAction::new("import-macro", None);
+	let clone = config.clone();
+	action.connect_activate(clone!(
+		#[weak]
+		window,
+		#[weak]
+		stack,
+		move |_, _| {
+			let config = clone.clone();
+			glib::spawn_future_local(async move {
+				let Some(path) = file_dialog("Import macro")
+					.open_future(Some(&window))
+					.await
+					.ok()
+					.and_then(|file| file.path())
+				else {
+					return;
+				};
+
+				if let Err(e) = import_file(&window, &stack, config, path).await {
+					tracing::error!("could not import macro: {e:#}");
+					dialogs::error_dialog(window.clone(), "Could not import macro", format!("{e:#}")).await;
+				}
+			});
+		}
+	));
+	window.add_action(&action);
+
+	let action = gtk::gio::SimpleAction::new("export-macro", None);
+	action.connect_activate(clone!(
+		#[weak]
+		window,
+		move |_, _| {
+			let file = export(&config.lock().unwrap());
+			glib::spawn_future_local(async move {
+				let dialog = file_dialog("Export macro");
+				dialog.set_initial_name(Some("macro.json"));
+				let Some(path) = dialog
+					.save_future(Some(&window))
+					.await
+					.ok()
+					.and_then(|file| file.path())
+				else {
+					return;
+				};
+
+				if let Err(e) = file.save(&path) {
+					tracing::error!("could not export macro: {e:#}");
+					dialogs::error_dialog(window.clone(), "Could not export macro", format!("{e:#}")).await;
+				}
+			});
+		}
+	));
+	window.add_action(&action);
+}
@@ -0,0 +1,391 @@
+use anyhow::{Context, anyhow};
+use gtk::{
+	ApplicationWindow, Stack, StringList,
+	glib::{self, clone},
+};
+use gtk4 as gtk;
+use libadwaita::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use super::{Config, dialogs, settings};
+use crate::shortcuts::{self, Shortcut, ShortcutAction};
+
+#[allow(non_upper_case_globals)]
+const default_profile: &str = "Default";
+
+/// Everything the GUI remembers between launches, stored in the confy "app-data" file
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct AppData {
+	/// Name of the profile shown in the window
+	pub profile: String,
+	pub profiles: BTreeMap<String, Config>,
+	#[serde(default = "shortcuts::default_shortcuts")]
+	pub shortcuts: Vec<Shortcut>,
+}
+
+impl Default for AppData {
+	fn default() -> Self {
+		return Self {
+			profile: default_profile.to_string(),
+			profiles: BTreeMap::from([(default_profile.to_string(), Config::default())]),
+			shortcuts: shortcuts::default_shortcuts(),
+		};
+	}
+}
+
+/// Loads the app-data file, a file from before profiles existed becomes the default profile
+pub(super) fn load(window: &ApplicationWindow, class: &str) -> anyhow::Result<AppData> {
+	let mut data: AppData = match confy::load(class, Some("app-data")) {
+		Ok(o) => o,
+		Err(e) => match confy::load::<Config>(class, Some("app-data")) {
+			Ok(config) => {
+				tracing::info!("moved app-data into the {default_profile} profile");
+				AppData {
+					profile: default_profile.to_string(),
+					profiles: BTreeMap::from([(default_profile.to_string(), config)]),
+					shortcuts: shortcuts::default_shortcuts(),
+				}
+			}
+			Err(_) => {
+				tracing::error!("could not load app-data: {e}");
+				// kept so the profiles and shortcuts in it can be fixed by hand and restored
+				let path = confy::get_configuration_file_path(class, Some("app-data")).context("could not get config file path")?;
+				let backup = path.with_extension("bak");
+				std::fs::rename(&path, &backup).context("could not move app-data file")?;
+				glib::spawn_future_local(dialogs::error_dialog(
+					window.clone(),
+					"Could not load profiles",
+					format!("{e}\n\nThe profiles and shortcuts were moved to {} and the defaults are used instead.", backup.display()),
+				));
+				confy::load(class, Some("app-data")).context("could not load app-data")?
+			}
+		},
+	};
+
+	// the file may have been edited by hand
+	if !data.profiles.contains_key(&data.profile) {
+		data.profile = match data.profiles.keys().next() {
+			Some(name) => name.clone(),
+			None => {
+				data.profiles
+					.insert(default_profile.to_string(), Config::default());
+				default_profile.to_string()
+			}
+		};
+	}
+
+	return Ok(data);
+}
+
+async fn name_dialog(window: &ApplicationWindow, heading: &str, name: &str) -> Option<String> {
+	let entry = gtk::Entry::builder()
+		.text(name)
+		.activates_default(true)
+		.build();
+
+	let dialog = libadwaita::AlertDialog::new(Some(heading), None);
+	dialog.add_responses(&[("cancel", "Cancel"), ("save", "Save")]);
+	dialog.set_response_appearance("save", libadwaita::ResponseAppearance::Suggested);
+	dialog.set_default_response(Some("save"));
+	dialog.set_close_response("cancel");
+	dialog.set_extra_child(Some(&entry));
+
+	if dialog.choose_future(window).await != "save" {
+		return None;
+	}
+
+	return Some(entry.text().trim().to_string());
+}
+
+async fn delete_dialog(window: &ApplicationWindow, name: &str) -> bool {
+	let dialog = libadwaita::AlertDialog::new(Some("Delete profile?"), Some(&format!("\"{name}\" and all of its settings will be removed.")));
+	dialog.add_responses(&[("cancel", "Cancel"), ("delete", "Delete")]);
+	dialog.set_response_appearance("delete", libadwaita::ResponseAppearance::Destructive);
+	dialog.set_close_response("cancel");
+
+	return dialog.choose_future(window).await == "delete";
+}
+
+/// Profile dropdown of the header bar, kept in sync with the app-data
+#[derive(Clone)]
+pub(super) struct Profiles {
+	window: ApplicationWindow,
+	stack: Stack,
+	config: Arc<Mutex<Config>>,
+	data: Arc<Mutex<AppData>>,
+	class: String,
+	names: StringList,
+	pub dropdown: gtk::DropDown,
+	/// Set while the dropdown is refilled, so that does not switch profiles
+	updating: Rc<Cell<bool>>,
+}
+
+impl Profiles {
+	pub fn new(window: &ApplicationWindow, stack: &Stack, config: Arc<Mutex<Config>>, data: Arc<Mutex<AppData>>, class: String) -> Self {
+		let names = StringList::new(&[]);
+		let dropdown = gtk::DropDown::builder()
+			.model(&names)
+			.tooltip_text("Profile")
+			.build();
+
+		let profiles = Self {
+			window: window.clone(),
+			stack: stack.clone(),
+			config,
+			data,
+			class,
+			names,
+			dropdown,
+			updating: Rc::new(Cell::new(false)),
+		};
+		profiles.refresh();
+
+		let clone = profiles.clone();
+		profiles.dropdown.connect_selected_notify(move |dropdown| {
+			if clone.updating.get() {
+				return;
+			}
+
+			if let Some(name) = clone.names.string(dropdown.selected()) {
+				clone.switch(&name);
+			}
+		});
+
+		let clone = profiles.clone();
+		window.connect_close_request(move |_| {
+			clone.store();
+			return glib::Propagation::Proceed;
+		});
+
+		profiles.add_actions();
+		return profiles;
+	}
+
+	fn refresh(&self) {
+		let (names, selected) = {
+			let data = self.data.lock().unwrap();
+			let names: Vec<String> = data.profiles.keys().cloned().collect();
+			let selected = names
+				.iter()
+				.position(|name| *name == data.profile)
+				.unwrap_or(0);
+			(names, selected)
+		};
+
+		self.updating.set(true);
+		let names: Vec<&str> = names.iter().map(String::as_str).collect();
+		self.names.splice(0, self.names.n_items(), &names);
+		self.dropdown.set_selected(selected as u32);
+		self.updating.set(false);
+	}
+
+	fn store(&self) {
+		let mut data = self.data.lock().unwrap();
+		let name = data.profile.clone();
+		data.profiles
+			.insert(name, self.config.lock().unwrap().clone());
+
+		if let Err(e) = confy::store(self.class.as_str(), Some("app-data"), &*data) {
+			tracing::error!("could not store app-data: {e}");
+		}
+	}
+
+	/// Shows the settings of another profile, keeping the ones of the current profile
+	fn switch(&self, name: &str) {
+		self.store();
+		{
+			let mut data = self.data.lock().unwrap();
+			let Some(config) = data.profiles.get(name).cloned() else {
+				return;
+			};
+
+			data.profile = name.to_string();
+			*self.config.lock().unwrap() = config;
+		}
+
+		tracing::debug!("switched to profile {name}");
+		super::rebuild_pages(&self.window, &self.stack, self.config.clone());
+		self.refresh();
+		self.store();
+	}
+
+	fn check_name(&self, name: &str) -> anyhow::Result<()> {
+		if name.is_empty() {
+			return Err(anyhow!("The profile name must not be empty."));
+		}
+
+		if self.data.lock().unwrap().profiles.contains_key(name) {
+			return Err(anyhow!("A profile named \"{name}\" already exists."));
+		}
+
+		return Ok(());
+	}
+
+	/// Asks for the name of a new profile with the given settings and switches to it
+	async fn create(&self, heading: &str, name: &str, config: Config) {
+		let Some(name) = name_dialog(&self.window, heading, name).await else {
+			return;
+		};
+
+		if let Err(e) = self.check_name(&name) {
+			dialogs::error_dialog(self.window.clone(), "Could not create profile", e.to_string()).await;
+			return;
+		}
+
+		self.data
+			.lock()
+			.unwrap()
+			.profiles
+			.insert(name.clone(), config);
+		self.switch(&name);
+	}
+
+	async fn rename(&self) {
+		let current = self.data.lock().unwrap().profile.clone();
+		let Some(name) = name_dialog(&self.window, "Rename profile", &current).await else {
+			return;
+		};
+
+		if name == current {
+			return;
+		}
+
+		if let Err(e) = self.check_name(&name) {
+			dialogs::error_dialog(self.window.clone(), "Could not rename profile", e.to_string()).await;
+			return;
+		}
+
+		let rebind = {
+			let mut data = self.data.lock().unwrap();
+			if let Some(config) = data.profiles.remove(&current) {
+				data.profiles.insert(name.clone(), config);
+			}
+			data.profile = name.clone();
+
+			let mut rebind = false;
+			for shortcut in data.shortcuts.iter_mut() {
+				if shortcut.action == ShortcutAction::Profile(current.clone()) {
+					shortcut.action = ShortcutAction::Profile(name.clone());
+					rebind = true;
+				}
+			}
+			rebind
+		};
+		self.refresh();
+		self.store();
+		if rebind {
+			self.rebind_shortcuts().await;
+		}
+	}
+
+	async fn delete(&self) {
+		let (current, next) = {
+			let data = self.data.lock().unwrap();
+			let next = data
+				.profiles
+				.keys()
+				.find(|name| **name != data.profile)
+				.cloned();
+			(data.profile.clone(), next)
+		};
+
+		let Some(next) = next else {
+			dialogs::error_dialog(self.window.clone(), "Could not delete profile", "The last profile can't be deleted.".into()).await;
+			return;
+		};
+
+		if !delete_dialog(&self.window, &current).await {
+			return;
+		}
+
+		self.switch(&next);
+		let rebind = {
+			let mut data = self.data.lock().unwrap();
+			data.profiles.remove(&current);
+
+			let len = data.shortcuts.len();
+			data.shortcuts
+				.retain(|shortcut| shortcut.action != ShortcutAction::Profile(current.clone()));
+			data.shortcuts.len() != len
+		};
+		self.refresh();
+		self.store();
+		if rebind {
+			self.rebind_shortcuts().await;
+		}
+	}
+
+	/// Binds the shortcuts again after one of them changed its id
+	async fn rebind_shortcuts(&self) {
+		if settings().lock().unwrap().client.disable_global_shortcut {
+			return;
+		}
+
+		let shortcuts = self.data.lock().unwrap().shortcuts.clone();
+		if let Err(e) = shortcuts::start_session(&self.window, &shortcuts).await {
+			tracing::error!("could not bind shortcuts: {e}");
+			dialogs::error_dialog(self.window.clone(), "Could not bind shortcuts", e.to_string()).await;
+		}
+	}
+
+	/// Adds the `win.new-profile`, `win.rename-profile`, `win.duplicate-profile` and `win.delete-profile` actions
+	fn add_actions(&self) {
+		let action = gtk::gio::SimpleAction::new("new-profile", None);
+		action.connect_activate(clone!(
+			#[strong(rename_to = profiles)]
+			self,
+			move |_, _| {
+				let profiles = profiles.clone();
+				glib::spawn_future_local(async move {
+					profiles.create("New profile", "", Config::default()).await;
+				});
+			}
+		));
+		self.window.add_action(&action);
+
+		let action = gtk::gio::SimpleAction::new("duplicate-profile", None);
+		action.connect_activate(clone!(
+			#[strong(rename_to = profiles)]
+			self,
+			move |_, _| {
+				let profiles = profiles.clone();
+				let name = format!("{} (copy)", profiles.data.lock().unwrap().profile);
+				let config = profiles.config.lock().unwrap().clone();
+				glib::spawn_future_local(async move {
+					profiles.create("Duplicate profile", &name, config).await;
+				});
+			}
+		));
+		self.window.add_action(&action);
+
+		let action = gtk::gio::SimpleAction::new("rename-profile", None);
+		action.connect_activate(clone!(
+			#[strong(rename_to = profiles)]
+			self,
+			move |_, _| {
+				let profiles = profiles.clone();
+				glib::spawn_future_local(async move {
+					profiles.rename().await;
+				});
+			}
+		));
+		self.window.add_action(&action);
+
+		let action = gtk::gio::SimpleAction::new("delete-profile", None);
+		action.connect_activate(clone!(
+			#[strong(rename_to = profiles)]
+			self,
+			move |_, _| {
+				let profiles = profiles.clone();
+				glib::spawn_future_local(async move {
+					profiles.delete().await;
+				});
+			}
+		));
+		self.window.add_action(&action);
+	}
+}
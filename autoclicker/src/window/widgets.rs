@@ -6,15 +6,19 @@ use gtk::{
 use gtk4 as gtk;
 use libadwaita::prelude::*;
 
+use common::prelude::{Message, Movement, Randomization};
+
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
-use std::{ops::Deref, str::FromStr};
 
 use super::{
-	ClickType,
 	Config,
 	MouseButton,
+	RoutePoint,
+	ScrollDirection,
 	dialogs,
 	events,
+	profiles::AppData,
 	// shortcut,
 	runtime,
 };
@@ -46,7 +50,152 @@ macro_rules! only_allow_numbers {
 	}};
 }
 
-pub fn start_clicking(window: &ApplicationWindow, config: Arc<Mutex<Config>>) -> gtk::Box {
+const MOUSE_BUTTONS: [&str; 8] = ["Left", "Right", "Middle", "Side", "Extra", "Forward", "Back", "Task"];
+
+fn mouse_button_index(button: MouseButton) -> u32 {
+	return match button {
+		MouseButton::Left => 0,
+		MouseButton::Right => 1,
+		MouseButton::Middle => 2,
+		MouseButton::Side => 3,
+		MouseButton::Extra => 4,
+		MouseButton::Forward => 5,
+		MouseButton::Back => 6,
+		MouseButton::Task => 7,
+	};
+}
+
+fn mouse_button_from_index(index: u32) -> MouseButton {
+	return match index {
+		0 => MouseButton::Left,
+		1 => MouseButton::Right,
+		2 => MouseButton::Middle,
+		3 => MouseButton::Side,
+		4 => MouseButton::Extra,
+		5 => MouseButton::Forward,
+		6 => MouseButton::Back,
+		7 => MouseButton::Task,
+		_ => {
+			panic!("how did this happen");
+		}
+	};
+}
+
+/// Spin button for the number of clicks per press, starting at `clicks`
+fn click_count(clicks: u32) -> gtk::SpinButton {
+	let spin = gtk::SpinButton::with_range(1.0, u32::MAX as f64, 1.0);
+	spin.set_digits(0);
+	spin.set_value(clicks.max(1) as f64);
+	return spin;
+}
+
+/// Largest random offset the daemon accepts
+#[allow(non_upper_case_globals)]
+const max_randomization: f64 = 60.0 * 60.0 * 1000.0;
+
+fn store_randomization(dropdown: &gtk::DropDown, first: &gtk::SpinButton, second: &gtk::SpinButton, config: &mut Config, field: fn(&mut Config) -> &mut Option<Randomization>) {
+	*field(config) = match dropdown.selected() {
+		0 => None,
+		1 => Some(Randomization::Uniform { range: first.value() }),
+		2 => Some(Randomization::Gaussian {
+			mean: first.value(),
+			stddev: second.value(),
+		}),
+		_ => {
+			panic!("how did this happen");
+		}
+	};
+
+	#[cfg(debug_assertions)]
+	tracing::debug!(?config);
+}
+
+/// Dropdown to pick a distribution, with fields for its parameters
+fn randomization(window: &ApplicationWindow, config: Arc<Mutex<Config>>, unit: &str, field: fn(&mut Config) -> &mut Option<Randomization>) -> gtk::Box {
+	let hbox = gtk::Box::builder()
+		.orientation(gtk::Orientation::Horizontal)
+		.spacing(6)
+		.build();
+
+	let dropdown = gtk::DropDown::new(Some(StringList::new(&["None", "Uniform", "Gaussian"])), Expression::NONE);
+	hbox.append(&dropdown);
+
+	let first = gtk::SpinButton::with_range(0.0, max_randomization, 1.0);
+	first.set_digits(2);
+	first.set_hexpand(true);
+	hbox.append(&first);
+
+	let second = gtk::SpinButton::with_range(0.0, max_randomization, 1.0);
+	second.set_digits(2);
+	second.set_hexpand(true);
+	second.set_tooltip_text(Some("Std dev"));
+	hbox.append(&second);
+
+	let label = gtk::Label::new(Some(unit));
+	label.set_hexpand(false);
+	label.set_halign(gtk::Align::End);
+	hbox.append(&label);
+
+	let update_fields = |selected: u32, first: &gtk::SpinButton, second: &gtk::SpinButton| {
+		first.set_visible(selected != 0);
+		second.set_visible(selected == 2);
+		// the mean can shift the offset either way, a range is always positive
+		if selected == 2 {
+			first.set_tooltip_text(Some("Mean"));
+			first.set_range(-max_randomization, max_randomization);
+		} else {
+			first.set_tooltip_text(Some("Range"));
+			first.set_range(0.0, max_randomization);
+		}
+	};
+
+	let current = field(&mut config.lock().unwrap()).clone();
+	match current {
+		None => dropdown.set_selected(0),
+		Some(Randomization::Uniform { range }) => {
+			dropdown.set_selected(1);
+			update_fields(1, &first, &second);
+			first.set_value(range);
+		}
+		Some(Randomization::Gaussian { mean, stddev }) => {
+			dropdown.set_selected(2);
+			update_fields(2, &first, &second);
+			first.set_value(mean);
+			second.set_value(stddev);
+		}
+	}
+	update_fields(dropdown.selected(), &first, &second);
+
+	let config_clone = config.clone();
+	dropdown.connect_selected_notify(clone!(
+		#[weak]
+		first,
+		#[weak]
+		second,
+		move |dropdown| {
+			update_fields(dropdown.selected(), &first, &second);
+			store_randomization(dropdown, &first, &second, &mut config_clone.lock().unwrap(), field);
+		}
+	));
+
+	for spin in [&first, &second] {
+		let config_clone = config.clone();
+		spin.connect_value_changed(clone!(
+			#[weak]
+			dropdown,
+			#[weak]
+			first,
+			#[weak]
+			second,
+			move |_| store_randomization(&dropdown, &first, &second, &mut config_clone.lock().unwrap(), field)
+		));
+		unfocus_on_enter!(window, spin);
+	}
+
+	return hbox;
+}
+
+pub fn start_clicking(window: &ApplicationWindow, config: Arc<Mutex<Config>>, data: Arc<Mutex<AppData>>) -> gtk::Box {
 	let container = gtk::Box::builder()
 		.orientation(gtk::Orientation::Vertical)
 		.spacing(12)
@@ -73,9 +222,41 @@ pub fn start_clicking(window: &ApplicationWindow, config: Arc<Mutex<Config>>) ->
 		}
 	));
 
+	let (sender, receiver) = async_channel::unbounded::<Message>();
+	runtime().spawn(async move {
+		// the daemon may not be running yet or could get restarted
+		while !sender.is_closed() {
+			if let Err(e) = crate::comm::subscribe(sender.clone()).await {
+				tracing::debug!("could not subscribe to daemon events: {e}");
+			}
+			tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+		}
+	});
+
+	glib::spawn_future_local(clone!(
+		#[weak]
+		window,
+		#[weak]
+		button,
+		async move {
+			while let Ok(event) = receiver.recv().await {
+				match event {
+					Message::JobFinished(e) => {
+						events::job_event(&button, e.id);
+					}
+					Message::JobFailed(e) if events::job_event(&button, e.id) => {
+						dialogs::error_dialog(window.clone(), "Job failed", e.msg).await;
+					}
+					_ => {}
+				}
+			}
+		}
+	));
+
 	let clone = config.clone();
 	window.connect_map(move |window| {
 		let clone = clone.clone();
+		let data = data.clone();
 
 		#[allow(clippy::await_holding_lock)]
 		gtk::glib::MainContext::default().spawn_local(glib::clone!(
@@ -101,11 +282,15 @@ pub fn start_clicking(window: &ApplicationWindow, config: Arc<Mutex<Config>>) ->
 				};
 
 				if !disable_global_shortcut {
-					crate::shortcuts::start_session(&window).await.unwrap();
-					crate::shortcuts::listen_events(move || {
+					let shortcuts = data.lock().unwrap().shortcuts.clone();
+					crate::shortcuts::start_session(&window, &shortcuts)
+						.await
+						.unwrap();
+					crate::shortcuts::listen_events(move |id| {
+						events::shortcut(&window, &button, clone.clone(), data.clone(), id);
 						if super::settings().lock().unwrap().client.notification {
 							let not = Notification::new("Autoclicker");
-							if button.label().unwrap() == "Start" {
+							if button.label().unwrap() == "Stop" {
 								not.set_body(Some("Autoclicker started!"));
 							} else {
 								not.set_body(Some("Autoclicker stopped."));
@@ -115,7 +300,6 @@ pub fn start_clicking(window: &ApplicationWindow, config: Arc<Mutex<Config>>) ->
 								.unwrap()
 								.send_notification(Some("dev.land.Autoclicker"), &not);
 						}
-						events::primary_button(&window, &button, clone.clone());
 					})
 					.await
 					.unwrap();
@@ -124,15 +308,12 @@ pub fn start_clicking(window: &ApplicationWindow, config: Arc<Mutex<Config>>) ->
 		));
 	});
 
-	let config_clone = config.clone();
 	window.connect_close_request(move |_| {
 		runtime().block_on(async {
 			if let Err(e) = crate::shortcuts::stop_session().await {
 				tracing::error!("could not close session: {e}");
 			}
 		});
-		let config: std::sync::MutexGuard<'_, Config> = config_clone.lock().unwrap();
-		confy::store("dev.land.Autoclicker", Some("app-data"), config.deref()).unwrap();
 
 		return glib::Propagation::Proceed;
 	});
@@ -307,12 +488,23 @@ pub fn click_position(window: &ApplicationWindow, config: Arc<Mutex<Config>>) ->
 			grid.attach(&set_pos_btn, 1, 1, 1, 1);
 		}
 
+		{
+			let random_label = gtk::Label::builder()
+				.label("Position randomness: ")
+				.halign(gtk::Align::Start)
+				.build();
+			grid.attach(&random_label, 0, 2, 1, 1);
+
+			let hbox = randomization(window, config.clone(), "px", |config| &mut config.mouse.position_random);
+			grid.attach(&hbox, 1, 2, 1, 1);
+		}
+
 		{
 			let int_label = gtk::Label::builder()
 				.label("Interval: ")
 				.halign(gtk::Align::Start)
 				.build();
-			grid.attach(&int_label, 0, 2, 1, 1);
+			grid.attach(&int_label, 0, 3, 1, 1);
 
 			let hbox = gtk::Box::builder()
 				.orientation(gtk::Orientation::Horizontal)
@@ -356,7 +548,103 @@ pub fn click_position(window: &ApplicationWindow, config: Arc<Mutex<Config>>) ->
 			label.set_halign(gtk::Align::End);
 			hbox.append(&label);
 
-			grid.attach(&hbox, 1, 2, 1, 1);
+			grid.attach(&hbox, 1, 3, 1, 1);
+		}
+
+		{
+			let random_label = gtk::Label::builder()
+				.label("Interval randomness: ")
+				.halign(gtk::Align::Start)
+				.build();
+			grid.attach(&random_label, 0, 4, 1, 1);
+
+			let hbox = randomization(window, config.clone(), "ms", |config| &mut config.mouse.interval_random);
+			grid.attach(&hbox, 1, 4, 1, 1);
+		}
+
+		{
+			let movement_label = gtk::Label::builder()
+				.label("Movement: ")
+				.halign(gtk::Align::Start)
+				.build();
+			grid.attach(&movement_label, 0, 5, 1, 1);
+
+			let hbox = gtk::Box::builder()
+				.orientation(gtk::Orientation::Horizontal)
+				.spacing(6)
+				.build();
+
+			let dropdown = gtk::DropDown::new(Some(StringList::new(&["Jump", "Eased", "Bezier"])), Expression::NONE);
+			hbox.append(&dropdown);
+
+			let entry = gtk::Entry::new();
+			entry.set_hexpand(true);
+			entry.set_placeholder_text(Some("Duration"));
+			hbox.append(&entry);
+
+			let label = gtk::Label::new(Some("ms"));
+			label.set_hexpand(false);
+			label.set_halign(gtk::Align::End);
+			hbox.append(&label);
+
+			match config.lock().unwrap().mouse.movement {
+				None => dropdown.set_selected(0),
+				Some(Movement::Eased { duration }) => {
+					dropdown.set_selected(1);
+					entry.set_text(duration.to_string().as_str());
+				}
+				Some(Movement::Bezier { duration }) => {
+					dropdown.set_selected(2);
+					entry.set_text(duration.to_string().as_str());
+				}
+			}
+			entry.set_visible(dropdown.selected() != 0);
+			label.set_visible(dropdown.selected() != 0);
+
+			let store = |dropdown: &gtk::DropDown, entry: &gtk::Entry, config: &mut Config| {
+				let duration = entry.text().parse().unwrap_or(200);
+				config.mouse.movement = match dropdown.selected() {
+					0 => None,
+					1 => Some(Movement::Eased { duration }),
+					2 => Some(Movement::Bezier { duration }),
+					_ => {
+						panic!("how did this happen");
+					}
+				};
+
+				#[cfg(debug_assertions)]
+				tracing::debug!(?config);
+			};
+
+			let config_clone = config.clone();
+			dropdown.connect_selected_notify(clone!(
+				#[weak]
+				entry,
+				#[weak]
+				label,
+				move |dropdown| {
+					entry.set_visible(dropdown.selected() != 0);
+					label.set_visible(dropdown.selected() != 0);
+					store(dropdown, &entry, &mut config_clone.lock().unwrap());
+				}
+			));
+
+			let config_clone = config.clone();
+			let focus_controller = EventControllerFocus::new();
+			focus_controller.connect_leave(clone!(
+				#[weak]
+				dropdown,
+				#[weak]
+				entry,
+				move |_| {
+					only_allow_numbers!(entry);
+					store(&dropdown, &entry, &mut config_clone.lock().unwrap());
+				}
+			));
+			entry.add_controller(focus_controller);
+			unfocus_on_enter!(window, entry);
+
+			grid.attach(&hbox, 1, 5, 1, 1);
 		}
 
 		container.append(&grid);
@@ -365,7 +653,7 @@ pub fn click_position(window: &ApplicationWindow, config: Arc<Mutex<Config>>) ->
 	return container;
 }
 
-pub fn click_type(config: Arc<Mutex<Config>>) -> gtk::Box {
+pub fn click_type(window: &ApplicationWindow, config: Arc<Mutex<Config>>) -> gtk::Box {
 	let container = gtk::Box::builder()
 		.orientation(gtk::Orientation::Vertical)
 		.spacing(12)
@@ -393,24 +681,13 @@ pub fn click_type(config: Arc<Mutex<Config>>) -> gtk::Box {
 				.build();
 			grid.attach(&button_label, 0, 0, 1, 1);
 
-			let button_dropdown = gtk::DropDown::new(Some(StringList::new(&["Left", "Right", "Middle"])), Expression::NONE);
-			button_dropdown.set_selected(match config.lock().unwrap().mouse.mouse_button {
-				MouseButton::Left => 0,
-				MouseButton::Right => 1,
-				MouseButton::Middle => 2,
-			});
+			let button_dropdown = gtk::DropDown::new(Some(StringList::new(&MOUSE_BUTTONS)), Expression::NONE);
+			button_dropdown.set_selected(mouse_button_index(config.lock().unwrap().mouse.mouse_button));
 
 			let config_clone = config.clone();
 			button_dropdown.connect_selected_notify(move |dropdown| {
 				let mut config = config_clone.lock().unwrap();
-				config.mouse.mouse_button = match dropdown.selected() {
-					0 => MouseButton::Left,
-					1 => MouseButton::Right,
-					2 => MouseButton::Middle,
-					_ => {
-						panic!("how did this happen");
-					}
-				};
+				config.mouse.mouse_button = mouse_button_from_index(dropdown.selected());
 
 				#[cfg(debug_assertions)]
 				tracing::debug!(?config);
@@ -421,33 +698,130 @@ pub fn click_type(config: Arc<Mutex<Config>>) -> gtk::Box {
 
 		{
 			let button_label = gtk::Label::builder()
-				.label("Type: ")
+				.label("Clicks: ")
 				.halign(gtk::Align::Start)
 				.build();
 			grid.attach(&button_label, 0, 1, 1, 1);
 
-			let button_dropdown = gtk::DropDown::new(Some(StringList::new(&["Single", "Double"])), Expression::NONE);
-			button_dropdown.set_selected(match config.lock().unwrap().mouse.typ {
-				ClickType::Single => 0,
-				ClickType::Double => 1,
-			});
+			let clicks_spin = click_count(config.lock().unwrap().mouse.clicks);
 
 			let config_clone = config.clone();
-			button_dropdown.connect_selected_notify(move |dropdown| {
+			clicks_spin.connect_value_changed(move |spin| {
 				let mut config = config_clone.lock().unwrap();
-				config.mouse.typ = match dropdown.selected() {
-					0 => ClickType::Single,
-					1 => ClickType::Double,
-					_ => {
-						panic!("how did this happen");
-					}
-				};
+				config.mouse.clicks = spin.value() as u32;
 
 				#[cfg(debug_assertions)]
 				tracing::debug!(?config);
 			});
+			unfocus_on_enter!(window, clicks_spin);
+
+			grid.attach(&clicks_spin, 1, 1, 1, 1);
+		}
+
+		{
+			let hold_label = gtk::Label::builder()
+				.label("Hold duration: ")
+				.halign(gtk::Align::Start)
+				.build();
+			grid.attach(&hold_label, 0, 2, 1, 1);
+
+			let hbox = gtk::Box::builder()
+				.orientation(gtk::Orientation::Horizontal)
+				.spacing(12)
+				.build();
+
+			let entry = gtk::Entry::new();
+			entry.set_hexpand(true);
+			entry.set_placeholder_text(Some("Duration"));
+			entry.set_text(
+				config
+					.lock()
+					.unwrap()
+					.mouse
+					.hold_duration
+					.to_string()
+					.as_str(),
+			);
+			let config_clone = config.clone();
+			let focus_controller = EventControllerFocus::new();
+			focus_controller.connect_leave(clone!(
+				#[weak]
+				entry,
+				move |_| {
+					only_allow_numbers!(entry);
+					let mut config = config_clone.lock().unwrap();
+					let num = entry.text();
+					if !num.is_empty() {
+						config.mouse.hold_duration = num.parse().unwrap();
+					}
+
+					#[cfg(debug_assertions)]
+					tracing::debug!(?config);
+				}
+			));
+			entry.add_controller(focus_controller);
+			unfocus_on_enter!(window, entry);
+			hbox.append(&entry);
+
+			let label = gtk::Label::new(Some("ms"));
+			label.set_hexpand(false);
+			label.set_halign(gtk::Align::End);
+			hbox.append(&label);
+
+			grid.attach(&hbox, 1, 2, 1, 1);
+		}
+
+		{
+			let gap_label = gtk::Label::builder()
+				.label("Click spacing: ")
+				.halign(gtk::Align::Start)
+				.build();
+			grid.attach(&gap_label, 0, 3, 1, 1);
+
+			let hbox = gtk::Box::builder()
+				.orientation(gtk::Orientation::Horizontal)
+				.spacing(12)
+				.build();
+
+			let entry = gtk::Entry::new();
+			entry.set_hexpand(true);
+			entry.set_placeholder_text(Some("Duration"));
+			entry.set_text(
+				config
+					.lock()
+					.unwrap()
+					.mouse
+					.click_spacing
+					.to_string()
+					.as_str(),
+			);
+			let config_clone = config.clone();
+			let focus_controller = EventControllerFocus::new();
+			focus_controller.connect_leave(clone!(
+				#[weak]
+				entry,
+				move |_| {
+					only_allow_numbers!(entry);
+					let mut config = config_clone.lock().unwrap();
+					let num = entry.text();
+					if !num.is_empty() {
+						config.mouse.click_spacing = num.parse().unwrap();
+					}
+
+					#[cfg(debug_assertions)]
+					tracing::debug!(?config);
+				}
+			));
+			entry.add_controller(focus_controller);
+			unfocus_on_enter!(window, entry);
+			hbox.append(&entry);
+
+			let label = gtk::Label::new(Some("ms"));
+			label.set_hexpand(false);
+			label.set_halign(gtk::Align::End);
+			hbox.append(&label);
 
-			grid.attach(&button_dropdown, 1, 1, 1, 1);
+			grid.attach(&hbox, 1, 3, 1, 1);
 		}
 
 		container.append(&grid);
@@ -898,8 +1272,511 @@ pub fn click_interval_keyboard(window: &ApplicationWindow, config: Arc<Mutex<Con
 			grid.attach(&hbox, 1, 3, 1, 1);
 		}
 
+		{
+			let random_label = gtk::Label::builder()
+				.label("Interval randomness: ")
+				.halign(gtk::Align::Start)
+				.build();
+			grid.attach(&random_label, 0, 4, 1, 1);
+
+			let hbox = randomization(window, config.clone(), "ms", |config| &mut config.keyboard.interval_random);
+			grid.attach(&hbox, 1, 4, 1, 1);
+		}
+
 		container.append(&grid);
 	}
 
 	return container;
 }
+
+pub fn scroll_direction(window: &ApplicationWindow, config: Arc<Mutex<Config>>) -> gtk::Box {
+	let container = gtk::Box::builder()
+		.orientation(gtk::Orientation::Vertical)
+		.spacing(12)
+		.build();
+
+	let title = gtk::Label::builder()
+		.label("Scrolling")
+		.halign(gtk::Align::Start)
+		.build();
+	title.add_css_class("title-4");
+	container.append(&title);
+
+	{
+		let grid = gtk::Grid::builder()
+			.row_spacing(6)
+			.column_spacing(6)
+			.column_homogeneous(true)
+			.row_homogeneous(true)
+			.build();
+
+		{
+			let direction_label = gtk::Label::builder()
+				.label("Direction: ")
+				.halign(gtk::Align::Start)
+				.build();
+			grid.attach(&direction_label, 0, 0, 1, 1);
+
+			let direction_dropdown = gtk::DropDown::new(Some(StringList::new(&["Up", "Down", "Left", "Right"])), Expression::NONE);
+			direction_dropdown.set_selected(match config.lock().unwrap().scroll.direction {
+				ScrollDirection::Up => 0,
+				ScrollDirection::Down => 1,
+				ScrollDirection::Left => 2,
+				ScrollDirection::Right => 3,
+			});
+
+			let config_clone = config.clone();
+			direction_dropdown.connect_selected_notify(move |dropdown| {
+				let mut config = config_clone.lock().unwrap();
+				config.scroll.direction = match dropdown.selected() {
+					0 => ScrollDirection::Up,
+					1 => ScrollDirection::Down,
+					2 => ScrollDirection::Left,
+					3 => ScrollDirection::Right,
+					_ => {
+						panic!("how did this happen");
+					}
+				};
+
+				#[cfg(debug_assertions)]
+				tracing::debug!(?config);
+			});
+
+			grid.attach(&direction_dropdown, 1, 0, 1, 1);
+		}
+
+		let unit = gtk::Label::new(Some(if config.lock().unwrap().scroll.hi_res {
+			"/120"
+		} else {
+			"notches"
+		}));
+
+		{
+			let distance_label = gtk::Label::builder()
+				.label("Distance per tick: ")
+				.halign(gtk::Align::Start)
+				.build();
+			grid.attach(&distance_label, 0, 1, 1, 1);
+
+			let hbox = gtk::Box::builder()
+				.orientation(gtk::Orientation::Horizontal)
+				.spacing(12)
+				.build();
+
+			let entry = gtk::Entry::new();
+			entry.set_hexpand(true);
+			entry.set_placeholder_text(Some("Distance"));
+			entry.set_text(config.lock().unwrap().scroll.distance.to_string().as_str());
+			let config_clone = config.clone();
+			let focus_controller = EventControllerFocus::new();
+			focus_controller.connect_leave(clone!(
+				#[weak]
+				entry,
+				move |_| {
+					only_allow_numbers!(entry);
+					let mut config = config_clone.lock().unwrap();
+					let num = entry.text();
+					match num.parse() {
+						Ok(0) | Err(_) => entry.set_text(config.scroll.distance.to_string().as_str()),
+						Ok(num) => config.scroll.distance = num,
+					}
+
+					#[cfg(debug_assertions)]
+					tracing::debug!(?config);
+				}
+			));
+			entry.add_controller(focus_controller);
+			unfocus_on_enter!(window, entry);
+			hbox.append(&entry);
+
+			unit.set_hexpand(false);
+			unit.set_halign(gtk::Align::End);
+			hbox.append(&unit);
+
+			grid.attach(&hbox, 1, 1, 1, 1);
+		}
+
+		{
+			let check = gtk::CheckButton::with_label("High-resolution scrolling");
+			check.set_active(config.lock().unwrap().scroll.hi_res);
+
+			let config_clone = config.clone();
+			check.connect_toggled(clone!(
+				#[weak]
+				unit,
+				move |btn| {
+					let mut config = config_clone.lock().unwrap();
+					config.scroll.hi_res = btn.is_active();
+					unit.set_label(if btn.is_active() {
+						"/120"
+					} else {
+						"notches"
+					});
+
+					#[cfg(debug_assertions)]
+					tracing::debug!(?config);
+				}
+			));
+
+			grid.attach(&check, 0, 2, 2, 1);
+		}
+
+		{
+			let int_label = gtk::Label::builder()
+				.label("Interval: ")
+				.halign(gtk::Align::Start)
+				.build();
+			grid.attach(&int_label, 0, 3, 1, 1);
+
+			let hbox = gtk::Box::builder()
+				.orientation(gtk::Orientation::Horizontal)
+				.spacing(12)
+				.build();
+
+			let entry = gtk::Entry::new();
+			entry.set_hexpand(true);
+			entry.set_placeholder_text(Some("Duration"));
+			entry.set_text(config.lock().unwrap().scroll.interval.to_string().as_str());
+			let config_clone = config.clone();
+			let focus_controller = EventControllerFocus::new();
+			focus_controller.connect_leave(clone!(
+				#[weak]
+				entry,
+				#[weak]
+				window,
+				move |_| {
+					only_allow_numbers!(entry);
+					let mut config = config_clone.lock().unwrap();
+					let num = entry.text();
+					if !num.is_empty() {
+						let num = num.parse().unwrap();
+						if num < 25 {
+							gtk::glib::MainContext::default().spawn_local(dialogs::short_duration_dialog(window.clone()));
+						} else {
+							config.scroll.interval = num;
+						}
+					}
+
+					#[cfg(debug_assertions)]
+					tracing::debug!(?config);
+				}
+			));
+			entry.add_controller(focus_controller);
+			unfocus_on_enter!(window, entry);
+			hbox.append(&entry);
+
+			let label = gtk::Label::new(Some("ms"));
+			label.set_hexpand(false);
+			label.set_halign(gtk::Align::End);
+			hbox.append(&label);
+
+			grid.attach(&hbox, 1, 3, 1, 1);
+		}
+
+		container.append(&grid);
+	}
+
+	return container;
+}
+
+pub fn scroll_repeat(window: &ApplicationWindow, config: Arc<Mutex<Config>>) -> gtk::Box {
+	let container = gtk::Box::builder()
+		.orientation(gtk::Orientation::Vertical)
+		.spacing(12)
+		.build();
+
+	let title = gtk::Label::builder()
+		.label("Repitition")
+		.halign(gtk::Align::Start)
+		.build();
+	title.add_css_class("title-4");
+	container.append(&title);
+
+	{
+		let grid = gtk::Grid::builder()
+			.row_spacing(6)
+			.column_spacing(6)
+			.column_homogeneous(true)
+			.row_homogeneous(true)
+			.build();
+
+		{
+			let radio1 = gtk::CheckButton::with_label("Scroll until stopped");
+			let radio2 = gtk::CheckButton::with_label("Scroll number of times: ");
+
+			radio2.set_group(Some(&radio1));
+
+			grid.attach(&radio1, 0, 0, 1, 1);
+			grid.attach(&radio2, 0, 1, 1, 1);
+
+			radio1.activate();
+
+			let entry = gtk::Entry::new();
+			grid.attach(&entry, 1, 1, 1, 1);
+
+			entry.set_sensitive(false);
+			entry.set_placeholder_text(Some("Amount"));
+			{
+				let lock = config.lock().unwrap();
+				if lock.scroll.repeat != 0 {
+					entry.set_text(lock.scroll.repeat.to_string().as_str());
+				}
+			}
+			unfocus_on_enter!(window, entry);
+
+			let config_clone = config.clone();
+			let focus_controller = EventControllerFocus::new();
+			focus_controller.connect_leave(clone!(
+				#[weak]
+				entry,
+				#[weak]
+				radio2,
+				move |_| {
+					only_allow_numbers!(entry);
+					let num = entry.text();
+
+					let mut config = config_clone.lock().unwrap();
+					config.scroll.repeat = if !num.is_empty() {
+						if radio2.is_active() {
+							num.parse().unwrap()
+						} else {
+							0
+						}
+					} else {
+						0
+					};
+
+					#[cfg(debug_assertions)]
+					tracing::debug!(?config);
+				}
+			));
+			entry.add_controller(focus_controller);
+
+			let config_clone = config.clone();
+			radio2.connect_toggled(clone!(
+				#[weak]
+				entry,
+				move |btn| {
+					let mut config = config_clone.lock().unwrap();
+					if !btn.is_active() {
+						config.scroll.repeat = 0;
+					} else {
+						let s = entry.text();
+						if !s.is_empty() {
+							config.scroll.repeat = s.parse().unwrap();
+						}
+					}
+
+					#[cfg(debug_assertions)]
+					tracing::debug!(?config);
+
+					entry.set_sensitive(btn.is_active());
+				}
+			));
+		}
+
+		container.append(&grid);
+	}
+
+	return container;
+}
+
+fn route_rows(window: &ApplicationWindow, config: Arc<Mutex<Config>>, rows: &gtk::Box) {
+	while let Some(child) = rows.first_child() {
+		rows.remove(&child);
+	}
+
+	let len = config.lock().unwrap().mouse.route.len();
+	for i in 0..len {
+		let (position, mouse_button, clicks, delay) = {
+			let lock = config.lock().unwrap();
+			let point = &lock.mouse.route[i];
+			(point.position, point.mouse_button, point.clicks, point.delay)
+		};
+
+		let hbox = gtk::Box::builder()
+			.orientation(gtk::Orientation::Horizontal)
+			.spacing(6)
+			.build();
+
+		let label = gtk::Label::new(Some(format!("{}. {}, {}", i + 1, position.0, position.1).as_str()));
+		label.set_hexpand(true);
+		label.set_halign(gtk::Align::Start);
+		hbox.append(&label);
+
+		let button_dropdown = gtk::DropDown::new(Some(StringList::new(&MOUSE_BUTTONS)), Expression::NONE);
+		button_dropdown.set_selected(mouse_button_index(mouse_button));
+		let config_clone = config.clone();
+		button_dropdown.connect_selected_notify(move |dropdown| {
+			let mut config = config_clone.lock().unwrap();
+			if let Some(point) = config.mouse.route.get_mut(i) {
+				point.mouse_button = mouse_button_from_index(dropdown.selected());
+			}
+
+			#[cfg(debug_assertions)]
+			tracing::debug!(?config);
+		});
+		hbox.append(&button_dropdown);
+
+		let clicks_spin = click_count(clicks);
+		clicks_spin.set_tooltip_text(Some("Clicks"));
+		let config_clone = config.clone();
+		clicks_spin.connect_value_changed(move |spin| {
+			let mut config = config_clone.lock().unwrap();
+			if let Some(point) = config.mouse.route.get_mut(i) {
+				point.clicks = spin.value() as u32;
+			}
+
+			#[cfg(debug_assertions)]
+			tracing::debug!(?config);
+		});
+		hbox.append(&clicks_spin);
+
+		let entry = gtk::Entry::new();
+		entry.set_width_chars(6);
+		entry.set_placeholder_text(Some("Delay"));
+		entry.set_text(delay.to_string().as_str());
+		let config_clone = config.clone();
+		let focus_controller = EventControllerFocus::new();
+		focus_controller.connect_leave(clone!(
+			#[weak]
+			entry,
+			move |_| {
+				only_allow_numbers!(entry);
+				let mut config = config_clone.lock().unwrap();
+				let num = entry.text();
+				if let Some(point) = config.mouse.route.get_mut(i).filter(|_| !num.is_empty()) {
+					point.delay = num.parse().unwrap();
+				}
+
+				#[cfg(debug_assertions)]
+				tracing::debug!(?config);
+			}
+		));
+		entry.add_controller(focus_controller);
+		unfocus_on_enter!(window, entry);
+		hbox.append(&entry);
+		hbox.append(&gtk::Label::new(Some("ms")));
+
+		let remove_btn = gtk::Button::from_icon_name("list-remove-symbolic");
+		let config_clone = config.clone();
+		remove_btn.connect_clicked(clone!(
+			#[weak]
+			window,
+			#[weak]
+			rows,
+			move |_| {
+				config_clone.lock().unwrap().mouse.route.remove(i);
+				route_rows(&window, config_clone.clone(), &rows);
+			}
+		));
+		hbox.append(&remove_btn);
+
+		rows.append(&hbox);
+	}
+}
+
+pub fn click_route(window: &ApplicationWindow, config: Arc<Mutex<Config>>) -> gtk::Box {
+	let container = gtk::Box::builder()
+		.orientation(gtk::Orientation::Vertical)
+		.spacing(12)
+		.build();
+
+	let title = gtk::Label::builder()
+		.label("Click route")
+		.halign(gtk::Align::Start)
+		.build();
+	title.add_css_class("title-4");
+	container.append(&title);
+
+	let hint = gtk::Label::builder()
+		.label("Points are clicked in order instead of the position above, each followed by its delay")
+		.halign(gtk::Align::Start)
+		.wrap(true)
+		.build();
+	hint.add_css_class("dim-label");
+	container.append(&hint);
+
+	let rows = gtk::Box::builder()
+		.orientation(gtk::Orientation::Vertical)
+		.spacing(6)
+		.build();
+	route_rows(window, config.clone(), &rows);
+	container.append(&rows);
+
+	{
+		let hbox = gtk::Box::builder()
+			.orientation(gtk::Orientation::Horizontal)
+			.spacing(6)
+			.homogeneous(true)
+			.build();
+
+		let add_btn = gtk::Button::with_label("Add position");
+		let (sender, receiver) = async_channel::bounded::<anyhow::Result<(i32, i32)>>(1);
+		add_btn.connect_clicked(move |btn| {
+			btn.set_label("Setting...");
+			runtime().spawn(clone!(
+				#[strong]
+				sender,
+				async move {
+					let res = events::get_coords().await;
+					sender.send(res).await.unwrap();
+				}
+			));
+		});
+
+		let config_clone = config.clone();
+		glib::spawn_future_local(clone!(
+			#[weak]
+			window,
+			#[weak]
+			rows,
+			#[weak]
+			add_btn,
+			async move {
+				while let Ok(response) = receiver.recv().await {
+					add_btn.set_label("Add position");
+					match response {
+						Ok(response) => {
+							{
+								let mut config = config_clone.lock().unwrap();
+								let point = RoutePoint {
+									position: response,
+									mouse_button: config.mouse.mouse_button,
+									clicks: config.mouse.clicks,
+									delay: config.mouse.interval,
+								};
+								config.mouse.route.push(point);
+								tracing::trace!(?response);
+							}
+							route_rows(&window, config_clone.clone(), &rows);
+						}
+
+						Err(e) => {
+							dialogs::error_dialog(window.clone(), "Command failed", e.to_string()).await;
+						}
+					}
+				}
+			}
+		));
+		hbox.append(&add_btn);
+
+		let clear_btn = gtk::Button::with_label("Clear");
+		let config_clone = config.clone();
+		clear_btn.connect_clicked(clone!(
+			#[weak]
+			window,
+			#[weak]
+			rows,
+			move |_| {
+				config_clone.lock().unwrap().mouse.route.clear();
+				route_rows(&window, config_clone.clone(), &rows);
+			}
+		));
+		hbox.append(&clear_btn);
+
+		container.append(&hbox);
+	}
+
+	return container;
+}
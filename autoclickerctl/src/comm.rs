@@ -0,0 +1,77 @@
+use anyhow::anyhow;
+
+use crate::settings;
+use common::prelude::*;
+
+#[cfg(feature = "dbus")]
+mod dbus;
+
+#[cfg(feature = "socket")]
+mod socket;
+
+pub(super) trait Method {
+	fn status() -> anyhow::Result<bool>;
+	fn send_message(msg: &Message) -> anyhow::Result<Message>;
+	fn watch(on_event: &mut dyn FnMut(Message) -> anyhow::Result<()>) -> anyhow::Result<()>;
+}
+
+fn is_dbus() -> bool {
+	return settings().lock().unwrap().general.communication_method == common::settings::latest::Methods::DBus;
+}
+
+fn request(msg: &Message) -> anyhow::Result<Message> {
+	if is_dbus() {
+		#[cfg(feature = "dbus")]
+		return dbus::DBus::send_message(msg);
+
+		#[cfg(not(feature = "dbus"))]
+		return Err(anyhow!("this build was not compiled with dbus support"));
+	}
+
+	#[cfg(feature = "socket")]
+	return socket::UnixSocket::send_message(msg);
+
+	#[cfg(not(feature = "socket"))]
+	return Err(anyhow!("this build was not compiled with unix socket support"));
+}
+
+pub fn send_message(msg: &Message) -> anyhow::Result<Message> {
+	let response = request(msg)?;
+	if let Message::Error(e) = response {
+		return Err(anyhow!(e.msg));
+	}
+
+	return Ok(response);
+}
+
+pub fn status() -> anyhow::Result<bool> {
+	if is_dbus() {
+		#[cfg(feature = "dbus")]
+		return dbus::DBus::status();
+
+		#[cfg(not(feature = "dbus"))]
+		return Err(anyhow!("this build was not compiled with dbus support"));
+	}
+
+	#[cfg(feature = "socket")]
+	return socket::UnixSocket::status();
+
+	#[cfg(not(feature = "socket"))]
+	return Err(anyhow!("this build was not compiled with unix socket support"));
+}
+
+pub fn watch(on_event: &mut dyn FnMut(Message) -> anyhow::Result<()>) -> anyhow::Result<()> {
+	if is_dbus() {
+		#[cfg(feature = "dbus")]
+		return dbus::DBus::watch(on_event);
+
+		#[cfg(not(feature = "dbus"))]
+		return Err(anyhow!("this build was not compiled with dbus support"));
+	}
+
+	#[cfg(feature = "socket")]
+	return socket::UnixSocket::watch(on_event);
+
+	#[cfg(not(feature = "socket"))]
+	return Err(anyhow!("this build was not compiled with unix socket support"));
+}
@@ -0,0 +1,73 @@
+use anyhow::Context;
+use zbus::{MatchRule, blocking::Connection, message, proxy};
+
+use common::prelude::*;
+
+#[proxy(interface = "dev.land.Autoclicker1", default_service = "dev.land.Autoclicker", default_path = "/dev/land/Autoclicker")]
+trait Daemon {
+	fn request(&self, msg: &str) -> zbus::Result<String>;
+}
+
+fn connection() -> anyhow::Result<Connection> {
+	return Connection::session().context("could not start dbus session");
+}
+
+pub(super) struct DBus;
+impl super::Method for DBus {
+	fn send_message(msg: &Message) -> anyhow::Result<Message> {
+		let connection = connection()?;
+		let proxy = DaemonProxyBlocking::new(&connection).context("could not create dbus proxy")?;
+
+		let json = Message::encode(msg).context("could not encode as json")?;
+		let response = proxy.request(&json).context("could not send request")?;
+
+		return Message::decode(response).context("could not decode json");
+	}
+
+	fn watch(on_event: &mut dyn FnMut(Message) -> anyhow::Result<()>) -> anyhow::Result<()> {
+		let connection = connection()?;
+		let rule = MatchRule::builder()
+			.msg_type(message::Type::Signal)
+			.interface("dev.land.Autoclicker1")?
+			.build();
+		let signals = zbus::blocking::MessageIterator::for_match_rule(rule, &connection, None).context("could not subscribe to signals")?;
+
+		on_event(Self::send_message(&Message::GetStatus(GetStatus {}))?)?;
+		for signal in signals {
+			let signal = signal.context("could not receive signal")?;
+			let header = signal.header();
+			let body = signal.body();
+			let event = match header.member().map(|m| m.as_str()) {
+				Some("JobStarted") => {
+					let (id, job): (u64, String) = body.deserialize().context("invalid signal")?;
+					Message::JobStarted(JobStarted { id, job })
+				}
+				Some("JobProgress") => {
+					let (id, job, clicked, amount): (u64, String, u64, u64) = body.deserialize().context("invalid signal")?;
+					Message::JobProgress(JobProgress { id, job, clicked, amount })
+				}
+				Some("JobFinished") => {
+					let (id, job, clicked): (u64, String, u64) = body.deserialize().context("invalid signal")?;
+					Message::JobFinished(JobFinished { id, job, clicked })
+				}
+				Some("JobFailed") => {
+					let (id, job, msg): (u64, String, String) = body.deserialize().context("invalid signal")?;
+					Message::JobFailed(JobFailed { id, job, msg })
+				}
+				_ => continue,
+			};
+			on_event(event)?;
+		}
+
+		return Ok(());
+	}
+
+	fn status() -> anyhow::Result<bool> {
+		let connection = connection()?;
+		let proxy = zbus::blocking::fdo::DBusProxy::new(&connection).context("could not start dbus proxy")?;
+
+		return proxy
+			.name_has_owner("dev.land.Autoclicker".try_into().unwrap())
+			.context("could not check if name has owner");
+	}
+}
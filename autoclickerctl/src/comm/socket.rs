@@ -0,0 +1,62 @@
+use anyhow::{Context, anyhow};
+use std::{
+	io::{BufRead, BufReader, Read, Write},
+	os::unix::net::UnixStream,
+};
+
+use crate::settings;
+use common::prelude::*;
+
+fn socket_file() -> anyhow::Result<String> {
+	let id = nix::unistd::geteuid();
+	let arc = settings();
+	let settings = arc.lock().unwrap();
+	let path = settings
+		.general
+		.socket_path
+		.as_ref()
+		.ok_or(anyhow!("socket_path must be supplied when communication_method = UnixSocket"))?;
+	return Ok(path.replace("$id", id.to_string().as_str()));
+}
+
+pub(super) struct UnixSocket;
+impl super::Method for UnixSocket {
+	fn send_message(msg: &Message) -> anyhow::Result<Message> {
+		let mut stream = UnixStream::connect(socket_file()?).context("could not connect to socket")?;
+		let json = Message::encode(msg).context("could not encode as json")?;
+		stream
+			.write_all(json.as_bytes())
+			.context("could not write to socket")?;
+
+		stream
+			.shutdown(std::net::Shutdown::Write)
+			.context("could not shutdown writing")?;
+		let mut msg = String::new();
+		stream
+			.read_to_string(&mut msg)
+			.context("could not read from socket")?;
+
+		return Message::decode(msg).context("could not decode json");
+	}
+
+	fn watch(on_event: &mut dyn FnMut(Message) -> anyhow::Result<()>) -> anyhow::Result<()> {
+		let mut stream = UnixStream::connect(socket_file()?).context("could not connect to socket")?;
+		let mut json = Message::encode(&Message::Subscribe(Subscribe {})).context("could not encode as json")?;
+		json.push('\n');
+		stream
+			.write_all(json.as_bytes())
+			.context("could not write to socket")?;
+
+		for line in BufReader::new(stream).lines() {
+			let line = line.context("could not read from socket")?;
+			on_event(Message::decode(line).context("could not decode json")?)?;
+		}
+
+		return Ok(());
+	}
+
+	fn status() -> anyhow::Result<bool> {
+		let s = socket_file()?;
+		return Ok(std::path::Path::new(&s).exists());
+	}
+}
@@ -0,0 +1,684 @@
+use anyhow::{Context, anyhow};
+use clap::{Parser, Subcommand, ValueEnum};
+use signal_hook::{
+	consts::{SIGINT, SIGTERM},
+	iterator::Signals,
+};
+use std::sync::{Arc, Mutex, OnceLock};
+
+#[allow(unused)]
+use tracing::{Level, debug, error, info, trace, warn};
+
+#[cfg(not(any(feature = "socket", feature = "dbus")))]
+compile_error!("either dbus or socket must be enabled");
+
+mod comm;
+mod tests;
+use common::{key_parser, prelude::*, xkb};
+
+const MOUSE_BUTTONS: [&str; 8] = ["left", "right", "middle", "side", "extra", "forward", "back", "task"];
+
+#[derive(ValueEnum, Clone, Debug)]
+enum Method {
+	#[value(name = "dbus")]
+	DBus,
+	#[value(name = "socket")]
+	UnixSocket,
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "autoclickerctl", version = version::version)]
+#[command(about = "Command-line client for autoclickerd", long_about = None)]
+struct Args {
+	#[arg(short, long, help = "increase verbosity")]
+	verbose: bool,
+
+	#[arg(short, long, help = "communication method, overrides the config file")]
+	method: Option<Method>,
+
+	#[command(subcommand)]
+	command: Command,
+}
+
+#[derive(clap::Args, Debug)]
+struct QueueArgs {
+	#[arg(short = 'q', long, help = "add the job to the queue instead of replacing every running job")]
+	enqueue: bool,
+
+	#[arg(short, long, requires = "enqueue", help = "name of the queued job")]
+	name: Option<String>,
+
+	#[arg(short, long, requires = "enqueue", help = "run at the same time as the previously queued job")]
+	parallel: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+	#[command(about = "Repeatedly click a mouse button")]
+	Click {
+		#[arg(short, long, default_value = "left", value_parser = MOUSE_BUTTONS)]
+		button: String,
+
+		#[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..), help = "clicks every interval, 2 for a double click, 3 for a triple click")]
+		clicks: u32,
+
+		#[arg(short, long, default_value_t = 0, help = "amount of clicks, 0 clicks until stopped")]
+		amount: u64,
+
+		#[arg(short, long, default_value_t = 25, help = "interval between clicks in ms")]
+		interval: u64,
+
+		#[arg(long, default_value_t = 0, help = "how long the button is held for every click in ms")]
+		hold_duration: u64,
+
+		#[arg(long, default_value_t = 50, help = "time between the clicks of a double or triple click in ms")]
+		click_spacing: u64,
+
+		#[arg(short, help = "x position to click at")]
+		x: Option<i32>,
+
+		#[arg(short, help = "y position to click at")]
+		y: Option<i32>,
+
+		#[arg(long, value_parser = parse_randomization, help = "random offset in ms added to every interval, uniform:RANGE or gaussian:MEAN,STDDEV")]
+		random_interval: Option<Randomization>,
+
+		#[arg(long, value_parser = parse_randomization, help = "random offset in pixels added to the position, uniform:RANGE or gaussian:MEAN,STDDEV")]
+		random_position: Option<Randomization>,
+
+		#[arg(
+			short,
+			long,
+			value_parser = parse_route_point,
+			conflicts_with_all = ["x", "y"],
+			help = "point of a click route, as X,Y[:BUTTON[:CLICKS[:DELAY]]], repeat to click several points in order. defaults to --button, --clicks and --interval"
+		)]
+		route: Vec<RoutePointArg>,
+
+		#[arg(long, value_parser = parse_movement, help = "move to every position along a path instead of jumping there, eased:DURATION or bezier:DURATION in ms")]
+		movement: Option<Movement>,
+
+		#[command(flatten)]
+		queue: QueueArgs,
+	},
+
+	#[command(about = "Repeatedly run a key sequence")]
+	Type {
+		#[arg(help = "key sequence, same syntax as the key sequence editor", required_unless_present = "file")]
+		sequence: Option<String>,
+
+		#[arg(short, long, conflicts_with = "sequence", help = "read the key sequence from a file")]
+		file: Option<std::path::PathBuf>,
+
+		#[arg(short, long, default_value_t = 1, help = "amount of repetitions, 0 repeats until stopped")]
+		amount: u64,
+
+		#[arg(short, long, default_value_t = 25, help = "interval between key presses in ms")]
+		interval: u64,
+
+		#[arg(short, long, default_value_t = 0, help = "delay before every repetition in ms")]
+		delay_before_repeat: u64,
+
+		#[arg(long, default_value_t = 0, help = "how long every key is held in ms")]
+		hold_duration: u64,
+
+		#[arg(long, value_parser = parse_randomization, help = "random offset in ms added to every interval, uniform:RANGE or gaussian:MEAN,STDDEV")]
+		random_interval: Option<Randomization>,
+
+		#[arg(short, long, help = "press enter after every repetition")]
+		enter: bool,
+
+		#[arg(short = 'P', long = "param", value_parser = parse_parameter, help = "value of a variable the key sequence uses, as NAME=VALUE, repeat for every variable")]
+		params: Vec<(String, String)>,
+
+		#[command(flatten)]
+		queue: QueueArgs,
+	},
+
+	#[command(about = "Hold a mouse button down")]
+	Hold {
+		#[arg(short, long, default_value = "left", value_parser = MOUSE_BUTTONS)]
+		button: String,
+
+		#[arg(short, long, default_value_t = 0, help = "how long to hold in ms, 0 holds until stopped")]
+		duration: u64,
+
+		#[arg(short, help = "x position to hold at")]
+		x: Option<i32>,
+
+		#[arg(short, help = "y position to hold at")]
+		y: Option<i32>,
+
+		#[command(flatten)]
+		queue: QueueArgs,
+	},
+
+	#[command(about = "Drag with a mouse button held down from one point to another")]
+	Drag {
+		#[arg(short, long, default_value = "left", value_parser = MOUSE_BUTTONS)]
+		button: String,
+
+		#[arg(long, value_parser = parse_point, help = "start position, as X,Y")]
+		from: (i32, i32),
+
+		#[arg(long, value_parser = parse_point, help = "end position, as X,Y")]
+		to: (i32, i32),
+
+		#[arg(short, long, default_value_t = 500, help = "how long the drag takes in ms")]
+		duration: u64,
+
+		#[command(flatten)]
+		queue: QueueArgs,
+	},
+
+	#[command(about = "Repeatedly turn the scroll wheel")]
+	Scroll {
+		#[arg(long, default_value = "vertical", value_parser = ["vertical", "horizontal"])]
+		axis: String,
+
+		#[arg(short, long, default_value_t = -1, allow_negative_numbers = true, help = "notches every tick, positive scrolls up or right")]
+		distance: i32,
+
+		#[arg(long, help = "distance is in 1/120 of a notch, for smooth scrolling")]
+		hi_res: bool,
+
+		#[arg(short, long, default_value_t = 0, help = "amount of ticks, 0 scrolls until stopped")]
+		amount: u64,
+
+		#[arg(short, long, default_value_t = 100, help = "interval between ticks in ms")]
+		interval: u64,
+
+		#[arg(long, value_parser = parse_randomization, help = "random offset in ms added to every interval, uniform:RANGE or gaussian:MEAN,STDDEV")]
+		random_interval: Option<Randomization>,
+
+		#[command(flatten)]
+		queue: QueueArgs,
+	},
+
+	#[command(about = "Record the physical keyboard and mouse into a macro")]
+	Record {
+		#[arg(short, long, help = "file to save the macro to, printed if not given")]
+		output: Option<std::path::PathBuf>,
+
+		#[arg(short, long, help = "stop after this many ms instead of waiting for enter")]
+		duration: Option<u64>,
+	},
+
+	#[command(about = "Run every job of a macro file")]
+	Run {
+		#[arg(help = "macro file, e.g. exported from the gui or written by the record command")]
+		file: std::path::PathBuf,
+
+		#[arg(short = 'q', long, help = "add the jobs to the queue instead of replacing every running job")]
+		enqueue: bool,
+
+		#[arg(short = 'P', long = "param", value_parser = parse_parameter, help = "value of a variable the key sequences use, as NAME=VALUE, repeat for every variable")]
+		params: Vec<(String, String)>,
+	},
+
+	#[command(about = "Stop a queued job, or every job if no id is given")]
+	Stop {
+		#[arg(help = "id of the job to stop")]
+		id: Option<u64>,
+	},
+
+	#[command(about = "List queued jobs")]
+	Jobs {
+		#[arg(short, long, help = "print the raw job list as json")]
+		json: bool,
+	},
+
+	#[command(about = "Show what the daemon is doing")]
+	Status {
+		#[arg(short, long, help = "print the raw status as json")]
+		json: bool,
+	},
+
+	#[command(about = "Print job events as newline-delimited json until interrupted")]
+	Watch,
+}
+
+fn parse_parameter(s: &str) -> Result<(String, String), String> {
+	let (name, value) = s.split_once('=').ok_or("expected NAME=VALUE")?;
+	return Ok((name.trim().to_string(), value.to_string()));
+}
+
+fn parse_point(s: &str) -> Result<(i32, i32), String> {
+	let (x, y) = s.split_once(',').ok_or("expected X,Y")?;
+	let x = x.trim().parse().map_err(|e| format!("{x}: {e}"))?;
+	let y = y.trim().parse().map_err(|e| format!("{y}: {e}"))?;
+	return Ok((x, y));
+}
+
+#[derive(Clone, Debug)]
+struct RoutePointArg {
+	position: (i32, i32),
+	button: Option<String>,
+	clicks: Option<u32>,
+	delay: Option<u64>,
+}
+
+fn parse_route_point(s: &str) -> Result<RoutePointArg, String> {
+	let mut parts = s.split(':');
+	let position = parse_point(parts.next().unwrap_or_default())?;
+
+	let button = parts.next().map(|b| b.trim().to_string());
+	if let Some(button) = button.as_deref().filter(|b| !MOUSE_BUTTONS.contains(b)) {
+		return Err(format!("invalid mouse button: {button}, expected one of {}", MOUSE_BUTTONS.join(", ")));
+	}
+
+	let clicks = parts
+		.next()
+		.map(|c| c.trim().parse().map_err(|e| format!("{c}: {e}")))
+		.transpose()?;
+	if clicks == Some(0) {
+		return Err("clicks must be at least 1".to_string());
+	}
+
+	let delay = parts
+		.next()
+		.map(|d| d.trim().parse().map_err(|e| format!("{d}: {e}")))
+		.transpose()?;
+
+	if parts.next().is_some() {
+		return Err("expected X,Y[:BUTTON[:CLICKS[:DELAY]]]".to_string());
+	}
+
+	return Ok(RoutePointArg { position, button, clicks, delay });
+}
+
+fn parse_movement(s: &str) -> Result<Movement, String> {
+	let (kind, duration) = s
+		.split_once(':')
+		.ok_or("expected eased:DURATION or bezier:DURATION")?;
+	let duration = duration
+		.trim()
+		.parse()
+		.map_err(|e| format!("{duration}: {e}"))?;
+
+	return match kind {
+		"eased" => Ok(Movement::Eased { duration }),
+		"bezier" => Ok(Movement::Bezier { duration }),
+		_ => Err(format!("unknown path: {kind}, expected eased or bezier")),
+	};
+}
+
+fn parse_randomization(s: &str) -> Result<Randomization, String> {
+	let (kind, args) = s
+		.split_once(':')
+		.ok_or("expected uniform:RANGE or gaussian:MEAN,STDDEV")?;
+	let args = args
+		.split(',')
+		.map(|n| n.trim().parse::<f64>().map_err(|e| format!("{n}: {e}")))
+		.collect::<Result<Vec<f64>, String>>()?;
+
+	return match (kind, args.as_slice()) {
+		("uniform", &[range]) => Ok(Randomization::Uniform { range }),
+		("gaussian", &[mean, stddev]) => Ok(Randomization::Gaussian { mean, stddev }),
+		_ => Err("expected uniform:RANGE or gaussian:MEAN,STDDEV".into()),
+	};
+}
+
+pub(crate) fn settings() -> Arc<Mutex<settings::Settings>> {
+	static SETTINGS: OnceLock<Arc<Mutex<settings::Settings>>> = OnceLock::new();
+	if SETTINGS.get().is_none() {
+		let conf = match settings::load() {
+			Ok(o) => o,
+			Err(e) => {
+				eprintln!("could not get settings: {e}");
+				std::process::exit(1);
+			}
+		};
+		return SETTINGS
+			.get_or_init(move || Arc::new(Mutex::new(conf)))
+			.clone();
+	}
+	return SETTINGS.get().unwrap().clone();
+}
+
+fn enable_logger() {
+	let subscriber = tracing_subscriber::fmt()
+		.compact()
+		.with_file(false)
+		.with_line_number(false)
+		.with_target(true)
+		.with_max_level(Level::TRACE)
+		.with_writer(std::io::stderr)
+		.without_time()
+		.finish();
+	tracing::subscriber::set_global_default(subscriber).unwrap();
+}
+
+fn queue_message(job: Job, queue: QueueArgs) -> Message {
+	if !queue.enqueue {
+		return job.into();
+	}
+
+	return Message::EnqueueJob(EnqueueJob {
+		name: queue.name,
+		job,
+		parallel: queue.parallel,
+	});
+}
+
+fn build_message(command: Command) -> anyhow::Result<Message> {
+	return Ok(match command {
+		Command::Click {
+			button,
+			clicks,
+			amount,
+			interval,
+			hold_duration,
+			click_spacing,
+			x,
+			y,
+			random_interval,
+			random_position,
+			route,
+			movement,
+			queue,
+		} => {
+			let route = route
+				.into_iter()
+				.map(|p| RoutePoint {
+					position: p.position,
+					button: p.button.unwrap_or_else(|| button.clone()),
+					clicks: p.clicks.unwrap_or(clicks),
+					delay: p.delay.unwrap_or(interval),
+				})
+				.collect();
+
+			queue_message(
+				Job::RepeatingMouseClick(RepeatingMouseClick {
+					button,
+					clicks,
+					amount,
+					interval,
+					hold_duration,
+					click_spacing,
+					position: (x, y),
+					interval_random: random_interval,
+					position_random: random_position,
+					route,
+					movement,
+				}),
+				queue,
+			)
+		}
+		Command::Type {
+			sequence,
+			file,
+			amount,
+			interval,
+			delay_before_repeat,
+			hold_duration,
+			random_interval,
+			enter,
+			params,
+			queue,
+		} => {
+			let raw = match file {
+				Some(path) => std::fs::read_to_string(&path).with_context(|| format!("could not read {}", path.display()))?,
+				None => sequence.unwrap_or_default(),
+			};
+
+			let mut seq = key_parser::parse_with(raw, &params.into_iter().collect())?;
+			if enter {
+				seq.extend([Actions::Press("KEY_ENTER".into()), Actions::Release("KEY_ENTER".into())]);
+			}
+
+			if seq.is_empty() {
+				return Err(anyhow!("key sequence is empty"));
+			}
+
+			queue_message(
+				Job::RepeatingKeyboardClick(RepeatingKeyboardClick {
+					buttons: seq,
+					amount,
+					interval,
+					delay_before_repeat,
+					hold_duration,
+					interval_random: random_interval,
+				}),
+				queue,
+			)
+		}
+		Command::Hold { button, duration, x, y, queue } => queue_message(
+			Job::MouseHold(MouseHold {
+				button,
+				duration,
+				position: (x, y),
+			}),
+			queue,
+		),
+		Command::Drag {
+			button,
+			from,
+			to,
+			duration,
+			queue,
+		} => queue_message(Job::MouseDrag(MouseDrag { button, from, to, duration }), queue),
+		Command::Scroll {
+			axis,
+			distance,
+			hi_res,
+			amount,
+			interval,
+			random_interval,
+			queue,
+		} => {
+			if distance == 0 {
+				return Err(anyhow!("scroll distance must not be 0"));
+			}
+
+			queue_message(
+				Job::MouseScroll(MouseScroll {
+					axis,
+					distance,
+					hi_res,
+					amount,
+					interval,
+					interval_random: random_interval,
+				}),
+				queue,
+			)
+		}
+		Command::Stop { id } => Message::StopClicking(StopClicking { job: id }),
+		Command::Status { .. } | Command::Jobs { .. } | Command::Watch | Command::Record { .. } | Command::Run { .. } => unreachable!(),
+	});
+}
+
+/// Jobs of a macro file with its parameters filled in, as they are queued on the daemon
+fn run_jobs(file: &std::path::Path, params: Vec<(String, String)>) -> anyhow::Result<Vec<EnqueueJob>> {
+	return MacroFile::load(file)?.resolve(&params.into_iter().collect());
+}
+
+fn print_status(status: &Status) {
+	println!("daemon: running");
+	let Some(job) = &status.job else {
+		println!("job: none");
+		return;
+	};
+
+	let state = if status.running {
+		"running"
+	} else {
+		"stopped"
+	};
+	match status.id {
+		Some(id) => println!("job: {job} #{id} ({state})"),
+		None => println!("job: {job} ({state})"),
+	}
+	if status.amount == 0 {
+		println!("clicked: {}", status.clicked);
+	} else {
+		println!("clicked: {}/{}", status.clicked, status.amount);
+	}
+	if job == "RepeatingKeyboardClick" {
+		println!("action: {}", status.index);
+	}
+	println!("elapsed: {}ms", status.elapsed);
+	if status.queued > 1 {
+		println!("queued: {}", status.queued);
+	}
+}
+
+fn print_jobs(list: &JobList) {
+	if list.jobs.is_empty() {
+		println!("no jobs queued");
+		return;
+	}
+
+	for job in &list.jobs {
+		let state = if job.running {
+			"running"
+		} else {
+			"queued"
+		};
+		let name = job.name.as_deref().unwrap_or("-");
+		let parallel = if job.parallel {
+			" (parallel)"
+		} else {
+			""
+		};
+		if job.amount == 0 {
+			println!("{}\t{name}\t{}\t{state}{parallel}\t{}", job.id, job.job, job.clicked);
+		} else {
+			println!("{}\t{name}\t{}\t{state}{parallel}\t{}/{}", job.id, job.job, job.clicked, job.amount);
+		}
+	}
+}
+
+fn main() -> anyhow::Result<std::process::ExitCode> {
+	let args = Args::parse();
+	if args.verbose {
+		enable_logger();
+		trace!("registered logger");
+	}
+
+	if let Some(method) = args.method {
+		settings().lock().unwrap().general.communication_method = match method {
+			Method::DBus => settings::latest::Methods::DBus,
+			Method::UnixSocket => settings::latest::Methods::UnixSocket,
+		};
+	}
+
+	if matches!(args.command, Command::Type { .. } | Command::Run { .. }) {
+		let layout = settings().lock().unwrap().general.keyboard_layout.clone();
+		key_parser::set_layout(xkb::Layout::load(&layout)?);
+	}
+
+	if let Command::Status { json } = args.command {
+		if !comm::status()? {
+			println!("daemon: not running");
+			return Ok(1.into());
+		}
+
+		let Message::Status(status) = comm::send_message(&Message::GetStatus(GetStatus {}))? else {
+			return Err(anyhow!("unexpected response from daemon"));
+		};
+
+		if json {
+			println!("{}", serde_json::to_string(&status)?);
+		} else {
+			print_status(&status);
+		}
+
+		return Ok(0.into());
+	}
+
+	if let Command::Jobs { json } = args.command {
+		let Message::JobList(list) = comm::send_message(&Message::ListJobs(ListJobs {}))? else {
+			return Err(anyhow!("unexpected response from daemon"));
+		};
+
+		if json {
+			println!("{}", serde_json::to_string(&list)?);
+		} else {
+			print_jobs(&list);
+		}
+
+		return Ok(0.into());
+	}
+
+	if let Command::Watch = args.command {
+		comm::watch(&mut |event| {
+			println!("{}", Message::encode(&event)?);
+			return Ok(());
+		})?;
+
+		return Ok(0.into());
+	}
+
+	if let Command::Record { output, duration } = args.command {
+		// ctrl+c and kill still stop the recording instead of leaving the daemon recording
+		let mut signals = Signals::new([SIGINT, SIGTERM]).context("could not register signal handlers")?;
+		let (stop_tx, stop_rx) = std::sync::mpsc::channel();
+		let signal_tx = stop_tx.clone();
+		std::thread::spawn(move || {
+			if signals.forever().next().is_some() {
+				let _ = signal_tx.send(());
+			}
+		});
+
+		comm::send_message(&Message::StartRecording(StartRecording {}))?;
+		match duration {
+			Some(duration) => {
+				eprintln!("recording for {duration}ms");
+				let _ = stop_rx.recv_timeout(std::time::Duration::from_millis(duration));
+			}
+			None => {
+				eprintln!("recording, press enter to stop");
+				std::thread::spawn(move || {
+					let _ = std::io::stdin().read_line(&mut String::new());
+					let _ = stop_tx.send(());
+				});
+				let _ = stop_rx.recv();
+			}
+		}
+
+		let Message::Recording(recording) = comm::send_message(&Message::StopRecording(StopRecording {}))? else {
+			return Err(anyhow!("unexpected response from daemon"));
+		};
+
+		let events = recording.events.len();
+		let file = MacroFile::new(vec![MacroJob::new(Job::ReplayMacro(ReplayMacro {
+			events: recording.events,
+			speed: 1.0,
+			amount: 1,
+			delay_before_repeat: 0,
+		}))]);
+		match output {
+			Some(path) => {
+				file.save(&path)?;
+				eprintln!("saved {events} events to {}", path.display());
+			}
+			None => println!("{}", file.to_json()?),
+		}
+
+		return Ok(0.into());
+	}
+
+	if let Command::Run { file, enqueue, params } = args.command {
+		let jobs = run_jobs(&file, params)?;
+		if !enqueue {
+			comm::send_message(&Message::StopClicking(StopClicking::default()))?;
+		}
+
+		for job in jobs {
+			if let Message::JobQueued(queued) = comm::send_message(&Message::EnqueueJob(job))? {
+				println!("{}", queued.id);
+			}
+		}
+
+		return Ok(0.into());
+	}
+
+	let message = build_message(args.command)?;
+	trace!(?message);
+	if let Message::JobQueued(queued) = comm::send_message(&message)? {
+		println!("{}", queued.id);
+	}
+
+	return Ok(0.into());
+}
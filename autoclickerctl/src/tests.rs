@@ -0,0 +1,126 @@
+#[cfg(test)]
+use super::*;
+
+#[cfg(test)]
+fn message(args: &[&str]) -> anyhow::Result<Message> {
+	let args = Args::try_parse_from(std::iter::once("autoclickerctl").chain(args.iter().copied()))?;
+	return build_message(args.command);
+}
+
+#[cfg(test)]
+fn macro_file(name: &str, json: &str) -> std::path::PathBuf {
+	let path = std::env::temp_dir().join(format!("autoclickerctl-{}-{name}.json", std::process::id()));
+	std::fs::write(&path, json).unwrap();
+	return path;
+}
+
+#[test]
+fn test_parse_args() {
+	assert_eq!(parse_parameter("user = alice=b"), Ok(("user".to_string(), " alice=b".to_string())));
+	assert!(parse_parameter("user").is_err());
+
+	assert_eq!(parse_point("10, -20"), Ok((10, -20)));
+	assert!(parse_point("10").is_err());
+
+	let point = parse_route_point("100,200:right:2:300").unwrap();
+	assert_eq!(
+		(point.position, point.button.as_deref(), point.clicks, point.delay),
+		((100, 200), Some("right"), Some(2), Some(300))
+	);
+	let point = parse_route_point("100,200").unwrap();
+	assert_eq!((point.button, point.clicks, point.delay), (None, None, None));
+	assert!(parse_route_point("100,200:nose").is_err());
+	assert!(parse_route_point("100,200:left:0").is_err());
+	assert!(parse_route_point("100,200:left:1:2:3").is_err());
+
+	assert!(matches!(parse_movement("bezier:400"), Ok(Movement::Bezier { duration: 400 })));
+	assert!(parse_movement("wobbly:400").is_err());
+
+	assert!(matches!(parse_randomization("uniform:15"), Ok(Randomization::Uniform { range }) if range == 15.0));
+	assert!(matches!(parse_randomization("gaussian:0,3"), Ok(Randomization::Gaussian { mean, stddev }) if mean == 0.0 && stddev == 3.0));
+	assert!(parse_randomization("gaussian:3").is_err());
+}
+
+#[test]
+fn test_click_message() {
+	let Message::RepeatingMouseClick(click) = message(&["click", "-b", "right", "-c", "2", "-a", "5", "-x", "500"]).unwrap() else {
+		panic!("expected a mouse click");
+	};
+	assert_eq!((click.button.as_str(), click.clicks, click.amount, click.position), ("right", 2, 5, (Some(500), None)));
+
+	// route points fall back to the click arguments
+	let Message::RepeatingMouseClick(click) = message(&["click", "-i", "300", "--route", "1,2", "--route", "3,4:middle:3:10"]).unwrap() else {
+		panic!("expected a mouse click");
+	};
+	assert_eq!((click.route[0].button.as_str(), click.route[0].clicks, click.route[0].delay), ("left", 1, 300));
+	assert_eq!((click.route[1].button.as_str(), click.route[1].clicks, click.route[1].delay), ("middle", 3, 10));
+
+	assert!(message(&["click", "--route", "1,2", "-x", "5"]).is_err());
+	assert!(message(&["click", "-c", "0"]).is_err());
+	assert!(message(&["click", "--name", "farm"]).is_err());
+}
+
+#[test]
+fn test_queue_message() {
+	let Message::EnqueueJob(enqueue) = message(&["scroll", "-q", "-n", "down", "-p", "-d", "-3"]).unwrap() else {
+		panic!("expected a queued job");
+	};
+	assert_eq!(enqueue.name.as_deref(), Some("down"));
+	assert!(enqueue.parallel);
+	assert!(matches!(enqueue.job, Job::MouseScroll(MouseScroll { distance: -3, .. })));
+
+	assert!(message(&["scroll", "-d", "0"]).is_err());
+	assert!(matches!(message(&["stop", "2"]), Ok(Message::StopClicking(StopClicking { job: Some(2) }))));
+}
+
+#[test]
+fn test_type_message() {
+	let Message::RepeatingKeyboardClick(keyboard) = message(&["type", "-e", "Tab"]).unwrap() else {
+		panic!("expected a keyboard job");
+	};
+	assert_eq!(
+		keyboard.buttons,
+		vec![
+			Actions::PressAndRelease("KEY_TAB".into()),
+			Actions::Press("KEY_ENTER".into()),
+			Actions::Release("KEY_ENTER".into()),
+		]
+	);
+
+	let Message::RepeatingKeyboardClick(keyboard) = message(&["type", "-P", "key=a", "\"${key}\""]).unwrap() else {
+		panic!("expected a keyboard job");
+	};
+	assert_eq!(keyboard.buttons, vec![Actions::PressAndRelease("KEY_A".into())]);
+
+	assert!(message(&["type", "\"${key}\""]).is_err());
+	assert!(message(&["type", ""]).is_err());
+	assert!(message(&["type", "repeat(0) { Tab }"]).is_err());
+	assert!(message(&["type"]).is_err());
+}
+
+#[test]
+fn test_run_jobs() {
+	let keyboard = |sequence: &str| {
+		format!(
+			r#"{{ "version": 1, "jobs": [{{ "type": "RepeatingKeyboardClick", "sequence": "{sequence}", "amount": 1, "interval": 25, "delay_before_repeat": 0, "hold_duration": 0 }}] }}"#
+		)
+	};
+
+	let path = macro_file("params", &keyboard(r#"\"${user}\" Tab"#));
+	let jobs = run_jobs(&path, vec![("user".into(), "ab".into())]).unwrap();
+	let Job::RepeatingKeyboardClick(job) = &jobs[0].job else {
+		panic!("expected a keyboard job");
+	};
+	assert_eq!(job.buttons.len(), 3);
+	assert!(run_jobs(&path, Vec::new()).is_err());
+	std::fs::remove_file(path).unwrap();
+
+	// the same sequences `type` refuses
+	for sequence in ["", "repeat(0) { Tab }"] {
+		let path = macro_file("empty", &keyboard(sequence));
+		assert!(run_jobs(&path, Vec::new()).is_err());
+		std::fs::remove_file(path).unwrap();
+	}
+
+	assert!(run_jobs(&std::env::temp_dir().join("autoclickerctl-missing.json"), Vec::new()).is_err());
+}
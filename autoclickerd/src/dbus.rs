@@ -1,10 +1,14 @@
 use anyhow::Context;
 use std::sync::Arc;
 use tokio::signal::unix::{SignalKind, signal};
-use tokio::sync::mpsc::Sender;
-use tracing::{info, trace};
-use zbus::{connection, interface};
+use tokio::sync::{broadcast, mpsc::Sender, oneshot};
+use tracing::{info, trace, warn};
+use zbus::{
+	connection, interface,
+	object_server::{InterfaceRef, SignalEmitter},
+};
 
+use super::Request;
 use common::prelude::*;
 
 struct Handler<F, O>
@@ -12,7 +16,7 @@ where
 	F: Fn(String) -> O + Send + Sync + 'static,
 	O: Future<Output = anyhow::Result<Message>> + Send + 'static,
 {
-	tx: Sender<Message>,
+	tx: Sender<Request>,
 	handler: Arc<F>,
 }
 
@@ -24,11 +28,14 @@ where
 	async fn func(&self, msg: &str) -> anyhow::Result<String> {
 		match (self.handler)(msg.to_string()).await {
 			Ok(o) => {
+				let (reply, response) = oneshot::channel();
 				self.tx
-					.send(o)
+					.send(Request { msg: o, reply })
 					.await
 					.context("could not send event over channel")?;
-				let message = Message::ConfirmResponse(ConfirmResponse {});
+				let message = response
+					.await
+					.context("background thread did not respond")?;
 				let json = Message::encode(&message)?;
 				return Ok(json);
 			}
@@ -40,6 +47,19 @@ where
 			}
 		}
 	}
+
+	async fn status(&self) -> Status {
+		let (reply, response) = oneshot::channel();
+		let msg = Message::GetStatus(GetStatus {});
+		if self.tx.send(Request { msg, reply }).await.is_err() {
+			return Status::default();
+		}
+
+		return match response.await {
+			Ok(Message::Status(status)) => status,
+			_ => Status::default(),
+		};
+	}
 }
 
 #[interface(name = "dev.land.Autoclicker1")]
@@ -54,9 +74,76 @@ where
 			Err(e) => format!("internal error: {e}"),
 		};
 	}
+
+	#[zbus(property)]
+	async fn running(&self) -> bool {
+		return self.status().await.running;
+	}
+
+	#[zbus(property)]
+	async fn click_count(&self) -> u64 {
+		return self.status().await.clicked;
+	}
+
+	#[zbus(property)]
+	async fn active_job(&self) -> String {
+		let status = self.status().await;
+		return if status.running {
+			status.job.unwrap_or_default()
+		} else {
+			String::new()
+		};
+	}
+
+	#[zbus(signal)]
+	async fn job_started(emitter: &SignalEmitter<'_>, id: u64, job: &str) -> zbus::Result<()>;
+
+	#[zbus(signal)]
+	async fn job_progress(emitter: &SignalEmitter<'_>, id: u64, job: &str, clicked: u64, amount: u64) -> zbus::Result<()>;
+
+	#[zbus(signal)]
+	async fn job_finished(emitter: &SignalEmitter<'_>, id: u64, job: &str, clicked: u64) -> zbus::Result<()>;
+
+	#[zbus(signal)]
+	async fn job_failed(emitter: &SignalEmitter<'_>, id: u64, job: &str, msg: &str) -> zbus::Result<()>;
+}
+
+async fn emit_event<F, O>(iface: &InterfaceRef<Handler<F, O>>, event: Message) -> zbus::Result<()>
+where
+	F: Fn(String) -> O + Send + Sync + 'static,
+	O: Future<Output = anyhow::Result<Message>> + Send + 'static,
+{
+	let emitter = iface.signal_emitter();
+	let handler = iface.get().await;
+	match event {
+		Message::JobStarted(event) => {
+			Handler::<F, O>::job_started(emitter, event.id, &event.job).await?;
+			handler.running_changed(emitter).await?;
+			handler.active_job_changed(emitter).await?;
+			handler.click_count_changed(emitter).await?;
+		}
+		Message::JobProgress(event) => {
+			Handler::<F, O>::job_progress(emitter, event.id, &event.job, event.clicked, event.amount).await?;
+			handler.click_count_changed(emitter).await?;
+		}
+		Message::JobFinished(event) => {
+			Handler::<F, O>::job_finished(emitter, event.id, &event.job, event.clicked).await?;
+			handler.running_changed(emitter).await?;
+			handler.active_job_changed(emitter).await?;
+			handler.click_count_changed(emitter).await?;
+		}
+		Message::JobFailed(event) => {
+			Handler::<F, O>::job_failed(emitter, event.id, &event.job, &event.msg).await?;
+			handler.running_changed(emitter).await?;
+			handler.active_job_changed(emitter).await?;
+		}
+		_ => {}
+	}
+
+	return Ok(());
 }
 
-pub async fn listen<F, O>(tx: Sender<Message>, handle_msg: Arc<F>) -> anyhow::Result<()>
+pub async fn listen<F, O>(tx: Sender<Request>, events: broadcast::Sender<Message>, handle_msg: Arc<F>) -> anyhow::Result<()>
 where
 	F: Fn(String) -> O + Send + Sync + 'static,
 	O: Future<Output = anyhow::Result<Message>> + Send + 'static,
@@ -66,12 +153,31 @@ where
 	let mut hup = signal(SignalKind::hangup()).unwrap();
 
 	let handler = Handler { tx, handler: handle_msg };
-	let _conn = connection::Builder::session()?
+	let conn = connection::Builder::session()?
 		.name("dev.land.Autoclicker")?
 		.serve_at("/dev/land/Autoclicker", handler)?
 		.build()
 		.await?;
 
+	let iface = conn
+		.object_server()
+		.interface::<_, Handler<F, O>>("/dev/land/Autoclicker")
+		.await?;
+	let mut events = events.subscribe();
+	tokio::spawn(async move {
+		loop {
+			match events.recv().await {
+				Ok(event) => {
+					if let Err(e) = emit_event(&iface, event).await {
+						warn!("could not emit dbus signal: {e}");
+					}
+				}
+				Err(broadcast::error::RecvError::Lagged(_)) => continue,
+				Err(broadcast::error::RecvError::Closed) => break,
+			}
+		}
+	});
+
 	info!("listening");
 	loop {
 		let int = int.recv();
@@ -0,0 +1,284 @@
+use anyhow::{Context, anyhow};
+use evdev_rs::enums::{EV_KEY, EventCode};
+use evdev_rs::{Device, DeviceWrapper, ReadFlag, ReadStatus};
+use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify};
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, mpsc};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc::Sender, oneshot};
+use tracing::{debug, error, info, warn};
+
+use crate::Request;
+use crate::record::{physical_device, physical_devices};
+use common::prelude::*;
+use common::settings::latest::EmergencyStopSettings;
+
+/// How long a device thread sleeps when there are no events
+#[allow(non_upper_case_globals)]
+const poll_interval: Duration = Duration::from_millis(10);
+
+enum KeyEvent {
+	/// A key was pressed, released or repeated
+	Key(EV_KEY, i32),
+	/// Whether a key is held after events were missed or its keyboard is gone, never counts as a press
+	Held(EV_KEY, bool),
+}
+
+/// Counts how often all keys of the combo were held down together
+struct Combo {
+	keys: Vec<EV_KEY>,
+	presses: usize,
+	within: Duration,
+	held: HashSet<EV_KEY>,
+	completed: VecDeque<Instant>,
+}
+
+impl Combo {
+	/// Returns `true` once the combo was pressed often enough
+	fn update(&mut self, key: EV_KEY, value: i32, now: Instant) -> bool {
+		if !self.keys.contains(&key) {
+			return false;
+		}
+
+		match value {
+			0 => {
+				self.held.remove(&key);
+				return false;
+			}
+			// the combo is completed by the key pressed last, key repeats do not count
+			1 => {
+				self.held.insert(key);
+			}
+			_ => return false,
+		}
+
+		if self.held.len() != self.keys.len() {
+			return false;
+		}
+
+		self.completed.push_back(now);
+		while self
+			.completed
+			.front()
+			.is_some_and(|t| now.duration_since(*t) > self.within)
+		{
+			self.completed.pop_front();
+		}
+
+		if self.completed.len() >= self.presses {
+			self.completed.clear();
+			return true;
+		}
+
+		return false;
+	}
+
+	fn set_held(&mut self, key: EV_KEY, held: bool) {
+		if !held {
+			self.held.remove(&key);
+		} else if self.keys.contains(&key) {
+			self.held.insert(key);
+		}
+	}
+}
+
+/// Sends the keys pressed on `device` until it is gone, returns `Ok` once nothing listens anymore
+fn read_keys(device: &Device, key_tx: &mpsc::Sender<KeyEvent>) -> std::io::Result<()> {
+	let mut syncing = false;
+	loop {
+		let flag = if syncing {
+			ReadFlag::SYNC
+		} else {
+			ReadFlag::NORMAL
+		};
+		let (synced, event) = match device.next_event(flag) {
+			Ok((ReadStatus::Success, event)) => (false, event),
+			// SYN_DROPPED, libevdev hands out what changed while events were dropped until it returns EAGAIN
+			Ok((ReadStatus::Sync, _)) if !syncing => {
+				syncing = true;
+				continue;
+			}
+			Ok((ReadStatus::Sync, event)) => (true, event),
+			Err(e) if e.kind() == std::io::ErrorKind::WouldBlock && syncing => {
+				syncing = false;
+				continue;
+			}
+			Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+				std::thread::sleep(poll_interval);
+				continue;
+			}
+			Err(e) => return Err(e),
+		};
+
+		let EventCode::EV_KEY(key) = event.event_code else {
+			continue;
+		};
+
+		let event = if synced {
+			KeyEvent::Held(key, event.value != 0)
+		} else {
+			KeyEvent::Key(key, event.value)
+		};
+		if key_tx.send(event).is_err() {
+			return Ok(());
+		}
+	}
+}
+
+/// Keyboards that are read for the emergency stop
+struct Keyboards {
+	keys: Vec<EV_KEY>,
+	key_tx: mpsc::Sender<KeyEvent>,
+	/// Paths of the keyboards being read, inotify reports the same device more than once
+	watched: Arc<Mutex<HashSet<PathBuf>>>,
+}
+
+impl Keyboards {
+	fn add_path(&self, path: PathBuf) -> anyhow::Result<()> {
+		if self.watched.lock().unwrap().contains(&path) {
+			return Ok(());
+		}
+
+		return match physical_device(&path) {
+			Some(device) => self.add(path, device),
+			None => Ok(()),
+		};
+	}
+
+	fn add(&self, path: PathBuf, device: Device) -> anyhow::Result<()> {
+		if !self
+			.keys
+			.iter()
+			.any(|key| device.has(EventCode::EV_KEY(*key)))
+		{
+			return Ok(());
+		}
+		if !self.watched.lock().unwrap().insert(path.clone()) {
+			return Ok(());
+		}
+
+		debug!("watching {:?} for the emergency stop", device.name());
+		let keys = self.keys.clone();
+		let key_tx = self.key_tx.clone();
+		let watched = self.watched.clone();
+		std::thread::Builder::new()
+			.name("emergency-stop".into())
+			.spawn(move || {
+				if let Err(e) = read_keys(&device, &key_tx) {
+					warn!("stopped watching {:?} for the emergency stop: {e}", device.name());
+				}
+
+				// keys held on a keyboard that is gone would never be released
+				for key in keys {
+					let _ = key_tx.send(KeyEvent::Held(key, false));
+				}
+				watched.lock().unwrap().remove(&path);
+			})
+			.context("could not start emergency stop thread")?;
+
+		return Ok(());
+	}
+}
+
+/// Watches every physical keyboard for the emergency stop combo and stops all jobs when it is pressed.
+/// Keys typed by the virtual keyboard never count, so a running macro can't trigger or block it
+pub fn watch(settings: &EmergencyStopSettings, tx: Sender<Request>) -> anyhow::Result<()> {
+	let keys = settings
+		.keys
+		.iter()
+		.map(|key| {
+			return key
+				.parse::<EV_KEY>()
+				.ok()
+				.filter(|_| key.starts_with("KEY_"))
+				.ok_or_else(|| anyhow!("unknown key in emergency_stop.keys: {key}"));
+		})
+		.collect::<anyhow::Result<Vec<EV_KEY>>>()?;
+
+	if keys.is_empty() || settings.presses == 0 {
+		return Err(anyhow!("emergency_stop needs at least one key and one press"));
+	}
+
+	// set up before looking for keyboards, so one plugged in meanwhile is not missed.
+	// udev makes new devices readable for the input group only after creating them, so changed permissions count too
+	let inotify = Inotify::init(InitFlags::IN_CLOEXEC).context("could not start inotify")?;
+	inotify
+		.add_watch("/dev/input", AddWatchFlags::IN_CREATE | AddWatchFlags::IN_ATTRIB)
+		.context("could not watch /dev/input")?;
+
+	let (key_tx, key_rx) = mpsc::channel();
+	let keyboards = Keyboards {
+		keys: keys.clone(),
+		key_tx,
+		watched: Arc::new(Mutex::new(HashSet::new())),
+	};
+	for (path, device) in physical_devices()? {
+		keyboards.add(path, device)?;
+	}
+	if keyboards.watched.lock().unwrap().is_empty() {
+		warn!("no keyboard could be opened in /dev/input yet, make sure the user is in the input group");
+	}
+
+	std::thread::Builder::new()
+		.name("emergency-stop".into())
+		.spawn(move || {
+			loop {
+				let events = match inotify.read_events() {
+					Ok(o) => o,
+					Err(e) => {
+						warn!("stopped watching /dev/input for new keyboards: {e}");
+						return;
+					}
+				};
+
+				for name in events.into_iter().filter_map(|event| event.name) {
+					if let Err(e) = keyboards.add_path(Path::new("/dev/input").join(name)) {
+						warn!("{e:#}");
+					}
+				}
+			}
+		})
+		.context("could not start emergency stop thread")?;
+
+	let mut combo = Combo {
+		keys,
+		presses: settings.presses as usize,
+		within: Duration::from_millis(settings.within),
+		held: HashSet::new(),
+		completed: VecDeque::new(),
+	};
+	std::thread::Builder::new()
+		.name("emergency-stop".into())
+		.spawn(move || {
+			for event in key_rx {
+				let pressed = match event {
+					KeyEvent::Key(key, value) => combo.update(key, value, Instant::now()),
+					KeyEvent::Held(key, held) => {
+						combo.set_held(key, held);
+						false
+					}
+				};
+				if !pressed {
+					continue;
+				}
+
+				info!("emergency stop pressed, stopping every job");
+				let (reply, _) = oneshot::channel();
+				if tx
+					.blocking_send(Request {
+						msg: Message::StopClicking(StopClicking { job: None }),
+						reply,
+					})
+					.is_err()
+				{
+					error!("could not send the emergency stop");
+					return;
+				}
+			}
+			debug!("stopped watching keyboards for the emergency stop");
+		})
+		.context("could not start emergency stop thread")?;
+
+	return Ok(());
+}
@@ -37,7 +37,7 @@ fn socket_file() -> anyhow::Result<String> {
 }
 
 #[inline]
-fn get_pos() -> anyhow::Result<(i32, i32)> {
+pub fn get_pos() -> anyhow::Result<(i32, i32)> {
 	let mut stream = UnixStream::connect(socket_file()?).context("could not connect to socket")?;
 	stream
 		.write(b"/cursorpos")
@@ -0,0 +1,726 @@
+use anyhow::anyhow;
+use evdev_rs::enums::{EV_KEY, EV_REL};
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
+use std::time::{Duration, Instant};
+use tracing::{debug, trace};
+
+use super::hypr;
+use crate::{
+	record::RELATIVE_AXES,
+	vkeyboard::Keyboard,
+	vmouse::{Mouse, MouseButton, ScrollAxis},
+};
+use common::prelude::*;
+
+/// Smallest amount of time between two actions of a job, so an interval of 0 does not flood the device
+#[allow(non_upper_case_globals)]
+const min_step: Duration = Duration::from_millis(1);
+
+fn sample(random: &Option<Randomization>) -> f64 {
+	let mut rng = rand::rng();
+	return match *random {
+		None => 0.0,
+		Some(Randomization::Uniform { range }) if range > 0.0 => rng.random_range(-range..=range),
+		Some(Randomization::Uniform { .. }) => 0.0,
+		Some(Randomization::Gaussian { mean, stddev }) => Normal::new(mean, stddev)
+			.map(|n| n.sample(&mut rng))
+			.unwrap_or(mean),
+	};
+}
+
+/// `ms` plus a random offset, never below zero
+fn randomized(ms: u64, random: &Option<Randomization>) -> Duration {
+	let randomized = ms as f64 + sample(random);
+	return Duration::try_from_secs_f64(randomized.max(0.0) / 1000.0).unwrap_or(Duration::from_millis(ms));
+}
+
+fn randomized_position(position: Option<i32>, random: &Option<Randomization>) -> Option<i32> {
+	return position.map(|p| p + sample(random).round() as i32);
+}
+
+/// Time between two relative motions of a drag
+#[allow(non_upper_case_globals)]
+const drag_tick: Duration = Duration::from_millis(10);
+
+/// How long a job without a deadline, like an endless `MouseHold`, sleeps before checking again
+#[allow(non_upper_case_globals)]
+const park_duration: Duration = Duration::from_secs(60 * 60);
+
+fn move_to(mouse: &Mouse, x: Option<i32>, y: Option<i32>, daemon_settings: &settings::latest::DaemonSettings) -> anyhow::Result<()> {
+	if x.is_none() && y.is_none() {
+		return Ok(());
+	}
+
+	if daemon_settings.hyprland_ipc && daemon_settings.mouse.pointer == settings::latest::PointerDevice::Relative && hypr::is_hyprland() {
+		hypr::move_mouse(mouse, x, y)?;
+	} else {
+		mouse.move_mouse(x, y)?;
+	}
+	mouse.track(x, y);
+
+	return Ok(());
+}
+
+/// Where the cursor currently is, as far as the daemon can tell
+fn cursor_position(mouse: &Mouse, daemon_settings: &settings::latest::DaemonSettings) -> Option<(i32, i32)> {
+	if daemon_settings.hyprland_ipc && hypr::is_hyprland() {
+		return hypr::get_pos().ok();
+	}
+
+	return mouse.last_position();
+}
+
+/// Corrects whatever pointer acceleration added to the relative motions of a path, where possible
+fn land(mouse: &Mouse, to: (i32, i32), daemon_settings: &settings::latest::DaemonSettings) -> anyhow::Result<()> {
+	if daemon_settings.mouse.pointer == settings::latest::PointerDevice::Absolute {
+		mouse.move_mouse(Some(to.0), Some(to.1))?;
+	} else if daemon_settings.hyprland_ipc && hypr::is_hyprland() {
+		let pos = hypr::get_pos()?;
+		if pos != to {
+			mouse.move_mouse_relative(Some(to.0 - pos.0), Some(to.1 - pos.1))?;
+		}
+	}
+
+	return Ok(());
+}
+
+/// Path the cursor travels along towards a click position, a cubic bezier curve walked with an ease-in-out curve
+struct Motion {
+	from: (f64, f64),
+	to: (i32, i32),
+	controls: [(f64, f64); 2],
+	steps: u64,
+	step: u64,
+	tick: Duration,
+}
+
+impl Motion {
+	fn new(from: (i32, i32), to: (i32, i32), movement: &Movement) -> Self {
+		let from = (from.0 as f64, from.1 as f64);
+		let delta = (to.0 as f64 - from.0, to.1 as f64 - from.1);
+		let along = |t: f64, bend: f64| (from.0 + delta.0 * t - delta.1 * bend, from.1 + delta.1 * t + delta.0 * bend);
+
+		let controls = match movement {
+			Movement::Eased { .. } => [along(1.0 / 3.0, 0.0), along(2.0 / 3.0, 0.0)],
+			Movement::Bezier { .. } => {
+				// bend away from the straight line by up to a third of its length, to either side
+				let mut rng = rand::rng();
+				[along(0.3, rng.random_range(-0.3..=0.3)), along(0.7, rng.random_range(-0.3..=0.3))]
+			}
+		};
+
+		let steps = (movement.duration() / drag_tick.as_millis() as u64).max(1);
+		return Self {
+			from,
+			to,
+			controls,
+			steps,
+			step: 0,
+			tick: Duration::from_millis(movement.duration()).div_f64(steps as f64),
+		};
+	}
+
+	fn is_done(&self) -> bool {
+		return self.step >= self.steps;
+	}
+
+	/// Where the cursor should be after the next step
+	fn next(&mut self) -> (i32, i32) {
+		self.step += 1;
+		if self.is_done() {
+			return self.to;
+		}
+
+		let t = self.step as f64 / self.steps as f64;
+		let t = if t < 0.5 {
+			4.0 * t * t * t
+		} else {
+			1.0 - (2.0 - 2.0 * t).powi(3) / 2.0
+		};
+		let u = 1.0 - t;
+		let [c1, c2] = self.controls;
+		let to = (self.to.0 as f64, self.to.1 as f64);
+		let point = |a: f64, b: f64, c: f64, d: f64| u * u * u * a + 3.0 * u * u * t * b + 3.0 * u * t * t * c + t * t * t * d;
+
+		return (point(self.from.0, c1.0, c2.0, to.0).round() as i32, point(self.from.1, c1.1, c2.1, to.1).round() as i32);
+	}
+}
+
+/// What a recorded event is played back through
+pub enum MacroCode {
+	Key(EV_KEY),
+	Button(MouseButton),
+	Relative(EV_REL),
+}
+
+pub fn macro_code(code: &str) -> anyhow::Result<MacroCode> {
+	if let Ok(axis) = code.parse::<EV_REL>() {
+		if RELATIVE_AXES.contains(&axis) {
+			return Ok(MacroCode::Relative(axis));
+		}
+	} else if let Ok(key) = code.parse::<EV_KEY>() {
+		if let Some(button) = MouseButton::from_key(key) {
+			return Ok(MacroCode::Button(button));
+		}
+		if code.starts_with("KEY_") {
+			return Ok(MacroCode::Key(key));
+		}
+	}
+
+	return Err(anyhow!("unsupported event in macro: {code}"));
+}
+
+/// Plays back one recorded event, keeping track of what it holds down
+fn replay_event(mouse: Option<&Mouse>, keyboard: Option<&Keyboard>, event: &MacroEvent, held_keys: &mut Vec<EV_KEY>, held_buttons: &mut Vec<MouseButton>) -> anyhow::Result<()> {
+	match macro_code(&event.code)? {
+		MacroCode::Key(key) => {
+			let keyboard = keyboard.unwrap();
+			if event.value == 0 {
+				keyboard.release_keyboard_button(key)?;
+				held_keys.retain(|&k| k != key);
+			} else {
+				keyboard.press_keyboard_button(key)?;
+				held_keys.push(key);
+			}
+		}
+		MacroCode::Button(button) => {
+			let mouse = mouse.unwrap();
+			if event.value == 0 {
+				mouse.release_mouse_button(button)?;
+				held_buttons.retain(|&b| b != button);
+			} else {
+				mouse.press_mouse_button(button)?;
+				held_buttons.push(button);
+			}
+		}
+		MacroCode::Relative(axis) => mouse.unwrap().move_relative_axis(axis, event.value)?,
+	}
+
+	return Ok(());
+}
+
+#[derive(Default)]
+struct Jitter {
+	samples: u64,
+	total: Duration,
+	max: Duration,
+}
+
+impl Jitter {
+	fn record(&mut self, jitter: Duration) {
+		self.samples += 1;
+		self.total += jitter;
+		self.max = self.max.max(jitter);
+	}
+}
+
+pub struct QueuedJob {
+	pub id: u64,
+	pub name: Option<String>,
+	pub job: Job,
+	pub parallel: bool,
+	pub started: Option<Instant>,
+	pub clicked: u64,
+	pub current_action: usize,
+	pub last_progress: Instant,
+	/// When the next action of this job is due, always advanced from the previous deadline so timing does not drift
+	pub next_due: Instant,
+
+	held_keys: Vec<EV_KEY>,
+	in_press_and_release: bool,
+	/// Mouse button that is currently held down by this job
+	held_button: Option<MouseButton>,
+	/// Mouse buttons a replayed macro is holding down
+	held_buttons: Vec<MouseButton>,
+	/// Presses already done as part of the current click, e.g. 1 halfway through a double click
+	presses: u32,
+	/// Deadline the current click started at, the interval is counted from here
+	click_start: Instant,
+	/// Relative motions done so far by a drag
+	drag_step: u64,
+	/// Where a drag or motion expects the cursor to be, so rounding errors do not add up
+	cursor: (i32, i32),
+	/// Path the cursor is on towards the position of the current click
+	motion: Option<Motion>,
+	/// High-resolution scroll distance that has not added up to a whole notch yet
+	scroll_remainder: i32,
+	/// Set when a job has nothing left to do, e.g. a key sequence without actions
+	finished: bool,
+	jitter: Jitter,
+}
+
+impl QueuedJob {
+	fn new(id: u64, name: Option<String>, job: Job, parallel: bool) -> Self {
+		return Self {
+			id,
+			name,
+			job,
+			parallel,
+			started: None,
+			clicked: 0,
+			current_action: 0,
+			last_progress: Instant::now(),
+			next_due: Instant::now(),
+			held_keys: Vec::new(),
+			in_press_and_release: false,
+			held_button: None,
+			held_buttons: Vec::new(),
+			presses: 0,
+			click_start: Instant::now(),
+			drag_step: 0,
+			cursor: (0, 0),
+			motion: None,
+			scroll_remainder: 0,
+			finished: false,
+			jitter: Jitter::default(),
+		};
+	}
+
+	pub fn start(&mut self, daemon_settings: &settings::latest::DaemonSettings) {
+		let now = Instant::now();
+		self.started = Some(now);
+		self.next_due = now;
+		let first = match self.job {
+			Job::MouseHold(_) | Job::MouseDrag(_) | Job::ReplayMacro(_) => Duration::ZERO,
+			Job::RepeatingMouseClick(ref click) => randomized(click.interval + daemon_settings.mouse.added_delay, &click.interval_random),
+			Job::MouseScroll(ref scroll) => randomized(scroll.interval + daemon_settings.mouse.added_delay, &scroll.interval_random),
+			Job::RepeatingKeyboardClick(ref click) => {
+				randomized(click.interval + daemon_settings.keyboard.added_delay, &click.interval_random).max(Duration::from_millis(click.delay_before_repeat))
+			}
+		};
+		self.schedule(first);
+	}
+
+	pub fn is_done(&self) -> bool {
+		let amount = self.job.amount();
+		return self.finished || (amount != 0 && self.clicked >= amount);
+	}
+
+	pub fn info(&self) -> JobInfo {
+		return JobInfo {
+			id: self.id,
+			name: self.name.clone(),
+			job: self.job.kind().to_string(),
+			parallel: self.parallel,
+			running: self.started.is_some(),
+			clicked: self.clicked,
+			amount: self.job.amount(),
+		};
+	}
+
+	pub fn status(&self) -> Status {
+		return Status {
+			job: Some(self.job.kind().to_string()),
+			id: Some(self.id),
+			running: self.started.is_some(),
+			clicked: self.clicked,
+			amount: self.job.amount(),
+			index: self.current_action,
+			elapsed: self
+				.started
+				.map(|s| s.elapsed().as_millis() as u64)
+				.unwrap_or(0),
+			queued: 0,
+		};
+	}
+
+	pub fn log_jitter(&self) {
+		if self.jitter.samples == 0 {
+			return;
+		}
+
+		let mean = self.jitter.total / self.jitter.samples as u32;
+		debug!(id = self.id, actions = self.jitter.samples, ?mean, max = ?self.jitter.max, "timing jitter");
+	}
+
+	/// Releases every key and mouse button this job is still holding down
+	pub fn release(&mut self, mouse: Option<&Mouse>, keyboard: Option<&Keyboard>) -> anyhow::Result<()> {
+		if !self.held_keys.is_empty() {
+			trace!(msg = "released keys implicitly", key = ?self.held_keys);
+		}
+
+		for key in &self.held_keys {
+			keyboard.unwrap().release_keyboard_button(*key)?;
+		}
+		self.held_keys.clear();
+		self.in_press_and_release = false;
+
+		if let Some(button) = self.held_button.take() {
+			trace!(msg = "released mouse button implicitly", ?button);
+			mouse.unwrap().release_mouse_button(button)?;
+		}
+		for button in self.held_buttons.drain(..) {
+			trace!(msg = "released mouse button implicitly", ?button);
+			mouse.unwrap().release_mouse_button(button)?;
+		}
+		self.presses = 0;
+
+		return Ok(());
+	}
+
+	fn schedule(&mut self, wait: Duration) {
+		self.next_due += wait.max(min_step);
+
+		let now = Instant::now();
+		if self.next_due < now {
+			// a whole step behind, e.g. the system was suspended. skip ahead instead of bursting to catch up
+			trace!(id = self.id, behind = ?(now - self.next_due), "resynchronizing");
+			self.next_due = now;
+		}
+	}
+
+	/// Moves on to the next keyboard action, waiting at least `delay_before_repeat` when the sequence wraps around
+	fn advance(&mut self, len: usize, delay_before_repeat: Duration, wait: Duration) {
+		self.current_action += 1;
+		if self.current_action == len {
+			self.clicked += 1;
+			self.current_action = 0;
+			self.schedule(wait.max(delay_before_repeat));
+			return;
+		}
+
+		self.schedule(wait);
+	}
+
+	pub fn step(&mut self, mouse: Option<&Mouse>, keyboard: Option<&Keyboard>, daemon_settings: &settings::latest::DaemonSettings) -> anyhow::Result<()> {
+		if self.is_done() {
+			return Ok(());
+		}
+
+		let jitter = Instant::now().saturating_duration_since(self.next_due);
+		self.jitter.record(jitter);
+		trace!(id = self.id, ?jitter);
+
+		match self.job {
+			Job::RepeatingMouseClick(ref click) => {
+				let mouse = mouse.unwrap();
+				let point = click.route.get(self.current_action);
+				let clicks = point.map(|p| p.clicks).unwrap_or(click.clicks);
+				if let Some(button) = self.held_button.take() {
+					mouse.release_mouse_button(button)?;
+					self.presses += 1;
+					if self.presses < clicks {
+						self.schedule(Duration::from_millis(click.click_spacing));
+						return Ok(());
+					}
+
+					self.presses = 0;
+					let interval = match point {
+						Some(point) => {
+							self.current_action += 1;
+							if self.current_action == click.route.len() {
+								self.current_action = 0;
+								self.clicked += 1;
+							}
+							point.delay
+						}
+						None => {
+							self.clicked += 1;
+							click.interval
+						}
+					};
+					let interval = randomized(interval + daemon_settings.mouse.added_delay, &click.interval_random);
+					self.next_due = self.click_start;
+					self.schedule(interval);
+					return Ok(());
+				}
+
+				if self.presses == 0 && self.motion.is_none() {
+					self.click_start = self.next_due;
+					let position = point
+						.map(|p| (Some(p.position.0), Some(p.position.1)))
+						.unwrap_or(click.position);
+					let x = randomized_position(position.0, &click.position_random);
+					let y = randomized_position(position.1, &click.position_random);
+
+					let start = match click.movement {
+						Some(_) if x.is_some() || y.is_some() => cursor_position(mouse, daemon_settings),
+						_ => None,
+					};
+					match (&click.movement, start) {
+						(Some(movement), Some(start)) => {
+							self.cursor = start;
+							self.motion = Some(Motion::new(start, (x.unwrap_or(start.0), y.unwrap_or(start.1)), movement));
+						}
+						_ => move_to(mouse, x, y, daemon_settings)?,
+					}
+				}
+
+				if let Some(mut motion) = self.motion.take().filter(|m| !m.is_done()) {
+					let target = motion.next();
+					mouse.move_mouse_relative(Some(target.0 - self.cursor.0), Some(target.1 - self.cursor.1))?;
+					self.cursor = target;
+					mouse.track(Some(target.0), Some(target.1));
+					if motion.is_done() {
+						land(mouse, target, daemon_settings)?;
+					}
+
+					let tick = motion.tick;
+					self.motion = Some(motion);
+					self.schedule(tick);
+					return Ok(());
+				}
+
+				let button: MouseButton = point.map(|p| &p.button).unwrap_or(&click.button).parse()?;
+				mouse.press_mouse_button(button)?;
+				self.held_button = Some(button);
+				self.schedule(Duration::from_millis(click.hold_duration));
+			}
+			Job::MouseHold(ref hold) => {
+				let mouse = mouse.unwrap();
+				if self.held_button.is_some() && hold.duration == 0 {
+					self.schedule(park_duration);
+					return Ok(());
+				}
+
+				if let Some(button) = self.held_button.take() {
+					mouse.release_mouse_button(button)?;
+					self.clicked += 1;
+					return Ok(());
+				}
+
+				move_to(mouse, hold.position.0, hold.position.1, daemon_settings)?;
+				let button: MouseButton = hold.button.parse()?;
+				mouse.press_mouse_button(button)?;
+				self.held_button = Some(button);
+				self.schedule(if hold.duration == 0 {
+					park_duration
+				} else {
+					Duration::from_millis(hold.duration)
+				});
+			}
+			Job::MouseDrag(ref drag) => {
+				let mouse = mouse.unwrap();
+				let steps = (drag.duration / drag_tick.as_millis() as u64).max(1);
+
+				if self.held_button.is_none() {
+					move_to(mouse, Some(drag.from.0), Some(drag.from.1), daemon_settings)?;
+					let button: MouseButton = drag.button.parse()?;
+					mouse.press_mouse_button(button)?;
+					self.held_button = Some(button);
+					self.cursor = drag.from;
+					self.drag_step = 0;
+					self.schedule(Duration::from_millis(drag.duration).div_f64(steps as f64));
+					return Ok(());
+				}
+
+				if self.drag_step < steps {
+					self.drag_step += 1;
+					let progress = self.drag_step as f64 / steps as f64;
+					let target = (
+						drag.from.0 + ((drag.to.0 - drag.from.0) as f64 * progress).round() as i32,
+						drag.from.1 + ((drag.to.1 - drag.from.1) as f64 * progress).round() as i32,
+					);
+					mouse.move_mouse_relative(Some(target.0 - self.cursor.0), Some(target.1 - self.cursor.1))?;
+					self.cursor = target;
+					mouse.track(Some(target.0), Some(target.1));
+					self.schedule(Duration::from_millis(drag.duration).div_f64(steps as f64));
+					return Ok(());
+				}
+
+				mouse.release_mouse_button(self.held_button.take().unwrap())?;
+				self.clicked += 1;
+			}
+			Job::MouseScroll(ref scroll) => {
+				let mouse = mouse.unwrap();
+				let axis: ScrollAxis = scroll.axis.parse()?;
+				if scroll.hi_res {
+					self.scroll_remainder += scroll.distance;
+					let notches = self.scroll_remainder / 120;
+					self.scroll_remainder %= 120;
+					mouse.scroll(axis, notches, scroll.distance)?;
+				} else {
+					mouse.scroll(axis, scroll.distance, scroll.distance.saturating_mul(120))?;
+				}
+
+				self.clicked += 1;
+				self.schedule(randomized(scroll.interval + daemon_settings.mouse.added_delay, &scroll.interval_random));
+			}
+			Job::ReplayMacro(ref replay) => {
+				if self.current_action == 0 {
+					self.click_start = self.next_due;
+				}
+
+				let offset = |event: &MacroEvent| {
+					return Duration::try_from_secs_f64(event.time as f64 / 1000.0 / replay.speed).unwrap_or(Duration::from_millis(event.time));
+				};
+				// everything recorded in the same millisecond is played back at once
+				let due = offset(&replay.events[self.current_action]);
+				while let Some(event) = replay
+					.events
+					.get(self.current_action)
+					.filter(|e| offset(e) <= due)
+				{
+					replay_event(mouse, keyboard, event, &mut self.held_keys, &mut self.held_buttons)?;
+					self.current_action += 1;
+				}
+
+				if self.current_action == replay.events.len() {
+					self.current_action = 0;
+					self.clicked += 1;
+					self.schedule(Duration::from_millis(replay.delay_before_repeat));
+					return Ok(());
+				}
+
+				self.next_due = self.click_start + offset(&replay.events[self.current_action]);
+			}
+			Job::RepeatingKeyboardClick(ref click) => {
+				let keyboard = keyboard.unwrap();
+				let len = click.buttons.len();
+				let interval = randomized(click.interval + daemon_settings.keyboard.added_delay, &click.interval_random);
+				let hold_duration = Duration::from_millis(click.hold_duration);
+				let delay_before_repeat = Duration::from_millis(click.delay_before_repeat);
+				let Some(action) = click.buttons.get(self.current_action).cloned() else {
+					self.finished = true;
+					return Ok(());
+				};
+
+				if self.in_press_and_release {
+					match action {
+						Actions::PressAndRelease(action) => {
+							let key = action.parse().unwrap();
+							keyboard.release_keyboard_button(key)?;
+							let pos = self.held_keys.iter().position(|&x| x == key);
+							self.held_keys.swap_remove(pos.unwrap());
+						}
+						Actions::Click(_) => {
+							if let Some(button) = self.held_button.take() {
+								mouse.unwrap().release_mouse_button(button)?;
+							}
+						}
+						_ => {}
+					}
+					self.in_press_and_release = false;
+					self.advance(len, delay_before_repeat, interval);
+					return Ok(());
+				}
+
+				let mut wait = interval;
+				match action {
+					Actions::PressAndRelease(action) => {
+						let Ok(key) = action.parse() else {
+							return Err(anyhow!("invalid keycode: {action}"));
+						};
+						keyboard.press_keyboard_button(key)?;
+						self.held_keys.push(key);
+						self.in_press_and_release = true;
+						self.schedule(hold_duration);
+						return Ok(());
+					}
+					Actions::Press(key) => {
+						let Ok(key) = key.parse() else {
+							return Err(anyhow!("invalid keycode: {key}"));
+						};
+						keyboard.press_keyboard_button(key)?;
+						self.held_keys.push(key);
+					}
+					Actions::Release(key) => {
+						let Ok(key) = key.parse() else {
+							return Err(anyhow!("invalid keycode: {key}"));
+						};
+						keyboard.release_keyboard_button(key)?;
+						if let Some(pos) = self.held_keys.iter().position(|&x| x == key) {
+							self.held_keys.swap_remove(pos);
+						}
+					}
+					Actions::Delay(delay) => {
+						wait = wait.max(Duration::from_millis(delay.max(0) as u64));
+					}
+					Actions::Click(button) => {
+						let button: MouseButton = button.parse()?;
+						mouse.unwrap().press_mouse_button(button)?;
+						self.held_button = Some(button);
+						self.in_press_and_release = true;
+						self.schedule(hold_duration);
+						return Ok(());
+					}
+					Actions::MouseDown(button) => {
+						let button: MouseButton = button.parse()?;
+						mouse.unwrap().press_mouse_button(button)?;
+						self.held_buttons.push(button);
+					}
+					Actions::MouseUp(button) => {
+						let button: MouseButton = button.parse()?;
+						mouse.unwrap().release_mouse_button(button)?;
+						self.held_buttons.retain(|&b| b != button);
+					}
+					Actions::Move(x, y) => move_to(mouse.unwrap(), Some(x), Some(y), daemon_settings)?,
+					Actions::MoveRelative(x, y) => {
+						let mouse = mouse.unwrap();
+						mouse.move_mouse_relative(Some(x), Some(y))?;
+						if let Some(last) = mouse.last_position() {
+							mouse.track(Some(last.0 + x), Some(last.1 + y));
+						}
+					}
+					Actions::Scroll(notches) => mouse
+						.unwrap()
+						.scroll(ScrollAxis::Vertical, notches, notches.saturating_mul(120))?,
+				}
+
+				self.advance(len, delay_before_repeat, wait);
+			}
+		}
+
+		return Ok(());
+	}
+}
+
+#[derive(Default)]
+pub struct Queue {
+	jobs: Vec<QueuedJob>,
+	next_id: u64,
+}
+
+impl Queue {
+	pub fn push(&mut self, name: Option<String>, job: Job, parallel: bool) -> u64 {
+		self.next_id += 1;
+		self.jobs
+			.push(QueuedJob::new(self.next_id, name, job, parallel));
+		return self.next_id;
+	}
+
+	pub fn len(&self) -> usize {
+		return self.jobs.len();
+	}
+
+	pub fn list(&self) -> Vec<JobInfo> {
+		return self.jobs.iter().map(|j| j.info()).collect();
+	}
+
+	fn active_len(&self) -> usize {
+		return match self.jobs.len() {
+			0 => 0,
+			n => 1 + self.jobs[1..n].iter().take_while(|j| j.parallel).count(),
+		};
+	}
+
+	/// The first job, plus every directly following job that was queued as parallel
+	pub fn active(&mut self) -> &mut [QueuedJob] {
+		let len = self.active_len();
+		return &mut self.jobs[..len];
+	}
+
+	/// Earliest deadline of all active jobs, jobs that have not been started yet are due right away
+	pub fn next_due(&self) -> Option<Instant> {
+		return self.jobs[..self.active_len()]
+			.iter()
+			.map(|j| {
+				if j.started.is_some() {
+					j.next_due
+				} else {
+					Instant::now()
+				}
+			})
+			.min();
+	}
+
+	pub fn remove(&mut self, id: u64) -> Option<QueuedJob> {
+		let pos = self.jobs.iter().position(|j| j.id == id)?;
+		return Some(self.jobs.remove(pos));
+	}
+
+	pub fn clear(&mut self) -> Vec<QueuedJob> {
+		return std::mem::take(&mut self.jobs);
+	}
+}
@@ -2,11 +2,11 @@ use anyhow::{Context, anyhow};
 use clap::Parser;
 use std::sync::{Arc, Mutex, OnceLock};
 use tokio::sync::{
-	Notify,
+	Notify, broadcast,
 	mpsc::{self, Receiver},
+	oneshot,
 };
 
-use evdev_rs::enums::EV_KEY;
 #[allow(unused)]
 use tracing::{Level, debug, error, info, trace, warn};
 
@@ -30,7 +30,10 @@ mod socket;
 #[cfg(feature = "dbus")]
 mod dbus;
 
+mod emergency;
 mod hypr;
+mod jobs;
+mod record;
 mod vdevice;
 mod vkeyboard;
 mod vmouse;
@@ -38,38 +41,183 @@ use common::prelude::*;
 use vkeyboard::*;
 use vmouse::*;
 
-async fn handle_msg(msg: String) -> anyhow::Result<Message> {
-	let req = Message::decode(msg)?;
-	trace!(?req);
+pub struct Request {
+	pub msg: Message,
+	pub reply: oneshot::Sender<Message>,
+}
 
-	match req {
-		Message::RepeatingMouseClick(ref event) => {
-			if settings().lock().unwrap().daemon.mouse.disabled {
-				return Err(anyhow!("mouse virtualization has been disabled in the configs"));
-			}
+/// Largest random offset in ms, anything above is surely a typo
+#[allow(non_upper_case_globals)]
+const max_randomization: f64 = 60.0 * 60.0 * 1000.0;
 
-			if !["left", "right", "middle"].contains(&event.button.as_str()) {
-				warn!("invalid mouse button");
-				return Err(anyhow!("invalid mouse button"));
-			}
+fn validate_randomization(random: &Option<Randomization>) -> anyhow::Result<()> {
+	match *random {
+		Some(Randomization::Uniform { range }) if !range.is_finite() || range < 0.0 => {
+			return Err(anyhow!("uniform range must be a positive number"));
+		}
+		Some(Randomization::Gaussian { mean, stddev }) if !mean.is_finite() || !stddev.is_finite() || stddev < 0.0 => {
+			return Err(anyhow!("gaussian mean must be a number and stddev a positive number"));
+		}
+		Some(Randomization::Uniform { range }) if range > max_randomization => {
+			return Err(anyhow!("uniform range must be at most {max_randomization}"));
+		}
+		Some(Randomization::Gaussian { mean, stddev }) if mean.abs() > max_randomization || stddev > max_randomization => {
+			return Err(anyhow!("gaussian mean and stddev must be at most {max_randomization}"));
+		}
+		_ => {}
+	}
+
+	return Ok(());
+}
+
+/// Longest drag or cursor movement in ms
+#[allow(non_upper_case_globals)]
+const max_motion_duration: u64 = 60 * 60 * 1000;
+
+fn validate_mouse_drag(event: &MouseDrag) -> anyhow::Result<()> {
+	validate_mouse_button(&event.button)?;
+
+	if event.duration > max_motion_duration {
+		return Err(anyhow!("drag duration must be at most {max_motion_duration}ms"));
+	}
+
+	return Ok(());
+}
+
+fn validate_mouse_button(button: &str) -> anyhow::Result<()> {
+	if settings().lock().unwrap().daemon.mouse.disabled {
+		return Err(anyhow!("mouse virtualization has been disabled in the configs"));
+	}
+
+	if button.parse::<MouseButton>().is_err() {
+		warn!("invalid mouse button");
+		return Err(anyhow!("invalid mouse button"));
+	}
+
+	return Ok(());
+}
+
+fn validate_mouse_scroll(event: &MouseScroll) -> anyhow::Result<()> {
+	if settings().lock().unwrap().daemon.mouse.disabled {
+		return Err(anyhow!("mouse virtualization has been disabled in the configs"));
+	}
+
+	if event.axis.parse::<ScrollAxis>().is_err() {
+		warn!("invalid scroll axis");
+		return Err(anyhow!("invalid scroll axis"));
+	}
 
-			if !["single", "double"].contains(&event.typ.as_str()) {
-				warn!("invalid click type");
-				return Err(anyhow!("invalid click type"));
+	if event.distance == 0 {
+		return Err(anyhow!("scroll distance must not be 0"));
+	}
+
+	validate_randomization(&event.interval_random)?;
+
+	return Ok(());
+}
+
+fn validate_mouse_click(event: &RepeatingMouseClick) -> anyhow::Result<()> {
+	validate_mouse_button(&event.button)?;
+
+	if event.clicks == 0 || event.route.iter().any(|p| p.clicks == 0) {
+		warn!("invalid click count");
+		return Err(anyhow!("click count must be at least 1"));
+	}
+
+	for point in &event.route {
+		validate_mouse_button(&point.button)?;
+	}
+
+	if event
+		.movement
+		.as_ref()
+		.is_some_and(|movement| movement.duration() > max_motion_duration)
+	{
+		return Err(anyhow!("movement duration must be at most {max_motion_duration}ms"));
+	}
+
+	validate_randomization(&event.interval_random)?;
+	validate_randomization(&event.position_random)?;
+
+	return Ok(());
+}
+
+fn validate_keyboard_click(event: &RepeatingKeyboardClick) -> anyhow::Result<()> {
+	if settings().lock().unwrap().daemon.keyboard.disabled {
+		return Err(anyhow!("keyboard virtualization has been disabled in the configs"));
+	}
+
+	if event.buttons.is_empty() {
+		return Err(anyhow!("key sequence has no actions"));
+	}
+
+	for action in event.buttons.iter().filter(|action| action.is_mouse()) {
+		match action {
+			Actions::Click(button) | Actions::MouseDown(button) | Actions::MouseUp(button) => validate_mouse_button(button)?,
+			_ if settings().lock().unwrap().daemon.mouse.disabled => {
+				return Err(anyhow!("mouse virtualization has been disabled in the configs"));
 			}
+			_ => {}
 		}
-		Message::RepeatingKeyboardClick(_) => {
-			if settings().lock().unwrap().daemon.keyboard.disabled {
+	}
+
+	validate_randomization(&event.interval_random)?;
+
+	return Ok(());
+}
+
+fn validate_replay(event: &ReplayMacro) -> anyhow::Result<()> {
+	if event.events.is_empty() {
+		return Err(anyhow!("macro has no events"));
+	}
+
+	if !(0.01..=100.0).contains(&event.speed) {
+		return Err(anyhow!("speed must be between 0.01 and 100"));
+	}
+
+	let daemon = settings().lock().unwrap().daemon.clone();
+	for e in &event.events {
+		match jobs::macro_code(&e.code)? {
+			jobs::MacroCode::Key(_) if daemon.keyboard.disabled => {
 				return Err(anyhow!("keyboard virtualization has been disabled in the configs"));
 			}
-		}
-		Message::StopClicking(_) => {}
-		_ => {
-			warn!("invalid request: {req:?}");
-			return Err(anyhow!("invalid request"));
+			jobs::MacroCode::Button(_) | jobs::MacroCode::Relative(_) if daemon.mouse.disabled => {
+				return Err(anyhow!("mouse virtualization has been disabled in the configs"));
+			}
+			_ => {}
 		}
 	}
 
+	return Ok(());
+}
+
+fn validate_job(job: &Job) -> anyhow::Result<()> {
+	return match job {
+		Job::RepeatingMouseClick(job) => validate_mouse_click(job),
+		Job::RepeatingKeyboardClick(job) => validate_keyboard_click(job),
+		Job::MouseHold(job) => validate_mouse_button(&job.button),
+		Job::MouseDrag(job) => validate_mouse_drag(job),
+		Job::MouseScroll(job) => validate_mouse_scroll(job),
+		Job::ReplayMacro(job) => validate_replay(job),
+	};
+}
+
+async fn handle_msg(msg: String) -> anyhow::Result<Message> {
+	let req = Message::decode(msg)?;
+	trace!(?req);
+
+	match req {
+		Message::EnqueueJob(ref event) => validate_job(&event.job)?,
+		Message::StopClicking(_) | Message::GetStatus(_) | Message::ListJobs(_) | Message::StartRecording(_) | Message::StopRecording(_) => {}
+		_ => match Job::try_from(req.clone()) {
+			Ok(job) => validate_job(&job)?,
+			Err(_) => {
+				warn!("invalid request: {req:?}");
+				return Err(anyhow!("invalid request"));
+			}
+		},
+	}
+
 	return Ok(req);
 }
 
@@ -91,186 +239,189 @@ fn settings() -> Arc<Mutex<settings::Settings>> {
 	return SETTINGS.get().unwrap().clone();
 }
 
-fn do_mouse_click(btn: &String, mouse: &Mouse) -> anyhow::Result<()> {
-	if btn == "left" {
-		mouse
-			.click_mouse_button(MouseButton::Left)
-			.context("could not click button")
-			.unwrap();
-	} else if btn == "right" {
-		mouse
-			.click_mouse_button(MouseButton::Right)
-			.context("could not click button")
-			.unwrap();
-	} else if btn == "middle" {
-		mouse
-			.click_mouse_button(MouseButton::Middle)
-			.context("could not click button")
-			.unwrap();
-	}
-
-	return Ok(());
-}
+/// How long before a deadline to stop relying on the tokio timer, which only has millisecond resolution,
+/// and sleep the rest on the thread instead
+#[allow(non_upper_case_globals)]
+const timer_slack: std::time::Duration = std::time::Duration::from_millis(2);
 
 #[allow(non_upper_case_globals)]
-const recv_timeout: std::time::Duration = std::time::Duration::from_millis(5);
+const progress_interval: std::time::Duration = std::time::Duration::from_millis(100);
 
-async fn bg_thread(exiting: Arc<Notify>, mut rx: Receiver<Message>, mouse: Option<Mouse>, keyboard: Option<Keyboard>) -> anyhow::Result<()> {
-	let mut last_message = Message::StopClicking(StopClicking {});
-	let mut last_click = std::time::Instant::now();
-	let mut amount_clicked: u128 = 0;
+fn emit(events: &broadcast::Sender<Message>, event: Message) {
+	trace!(?event);
+	// nobody listening is fine
+	let _ = events.send(event);
+}
 
-	let mut current_action: usize = 0;
-	let mut last_repeat = std::time::Instant::now();
-	let mut held_keys: Vec<EV_KEY> = Vec::new();
-	let mut in_press_and_release = false;
-	let mut delay_ms: Option<i64> = None;
+fn job_finished(events: &broadcast::Sender<Message>, job: &jobs::QueuedJob) {
+	emit(
+		events,
+		Message::JobFinished(JobFinished {
+			id: job.id,
+			job: job.job.kind().to_string(),
+			clicked: job.clicked,
+		}),
+	);
+}
+
+async fn bg_thread(exiting: Arc<Notify>, mut rx: Receiver<Request>, events: broadcast::Sender<Message>, mouse: Option<Mouse>, keyboard: Option<Keyboard>) -> anyhow::Result<()> {
+	let mut queue = jobs::Queue::default();
+	// status of the most recently removed job, reported while the queue is empty
+	let mut last_status = Status::default();
+	let mut recorder: Option<record::Recorder> = None;
 
 	let daemon_settings = settings().lock().unwrap().daemon.clone();
-	'outer: loop {
+	loop {
+		let deadline = queue.next_due();
+		let wake = deadline
+			.and_then(|d| d.checked_sub(timer_slack))
+			.unwrap_or_else(std::time::Instant::now);
 		tokio::select! {
 			biased;
 			_ = exiting.notified() => return Ok(()),
-			msg = rx.recv() => {
-				if let Some(msg) = msg {
+			req = rx.recv() => {
+				if let Some(req) = req {
 					trace!("got msg from channel");
-					last_click = std::time::Instant::now();
-					amount_clicked = 0;
-					last_message = msg;
-					if let Message::RepeatingKeyboardClick(_) = last_message {
-						current_action = 0;
+					let mut stopped: Vec<jobs::QueuedJob> = Vec::new();
+					let response = match req.msg {
+						Message::GetStatus(_) => {
+							let mut status = match queue.active().first() {
+								Some(job) => job.status(),
+								None => last_status.clone(),
+							};
+							status.queued = queue.len();
+							Message::Status(status)
+						}
+						Message::ListJobs(_) => Message::JobList(JobList { jobs: queue.list() }),
+						// a recording that stopped at its limits was abandoned by its client, so it can be replaced
+						Message::StartRecording(_) if recorder.as_ref().is_some_and(|r| !r.is_stopped()) => Message::Error(ErrorResponse { msg: "already recording".into() }),
+						Message::StartRecording(_) => match record::Recorder::start() {
+							Ok(o) => {
+								info!("started recording");
+								recorder = Some(o);
+								Message::ConfirmResponse(ConfirmResponse {})
+							}
+							Err(e) => Message::Error(ErrorResponse { msg: e.to_string() }),
+						},
+						Message::StopRecording(_) => match recorder.take() {
+							Some(recorder) => {
+								let events = tokio::task::block_in_place(|| recorder.stop());
+								info!("stopped recording, {} events", events.len());
+								Message::Recording(Recording { events })
+							}
+							None => Message::Error(ErrorResponse { msg: "not recording".into() }),
+						},
+						Message::StopClicking(StopClicking { job: None }) => {
+							stopped = queue.clear();
+							Message::ConfirmResponse(ConfirmResponse {})
+						}
+						Message::StopClicking(StopClicking { job: Some(id) }) => match queue.remove(id) {
+							Some(job) => {
+								stopped.push(job);
+								Message::ConfirmResponse(ConfirmResponse {})
+							}
+							None => Message::Error(ErrorResponse { msg: format!("no job with id {id}") }),
+						},
+						Message::EnqueueJob(enqueue) => {
+							let id = queue.push(enqueue.name, enqueue.job, enqueue.parallel);
+							Message::JobQueued(JobQueued { id })
+						}
+						// a job sent on its own replaces everything that is queued
+						msg => match Job::try_from(msg) {
+							Ok(job) => {
+								stopped = queue.clear();
+								let id = queue.push(None, job, false);
+								Message::JobQueued(JobQueued { id })
+							}
+							Err(_) => Message::Error(ErrorResponse { msg: "invalid request".into() }),
+						},
+					};
+
+					for mut job in stopped {
+						if let Err(e) = job.release(mouse.as_ref(), keyboard.as_ref()) {
+							error!("could not release keys of job {}: {e}", job.id);
+						}
+						if job.started.is_some() {
+							job.log_jitter();
+							job_finished(&events, &job);
+							last_status = job.status();
+							last_status.running = false;
+						}
 					}
+
+					let _ = req.reply.send(response);
 				} else {
 					warn!("recved nothing from channel");
 					return Ok(());
 				}
 			}
-			_ = tokio::time::sleep(recv_timeout) => {}
+			_ = tokio::time::sleep_until(wake.into()), if deadline.is_some() => {}
 		}
 
-		match last_message {
-			Message::StopClicking(_) => {
-				if !held_keys.is_empty() {
-					trace!(msg = "released keys implicitly", key = ?held_keys);
-				}
-
-				for key in &held_keys {
-					keyboard.as_ref().unwrap().release_keyboard_button(*key)?;
-				}
-				held_keys.clear();
-				in_press_and_release = false;
-				delay_ms = None;
+		if let Some(deadline) = deadline {
+			let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+			if !remaining.is_zero() && remaining <= timer_slack {
+				tokio::task::block_in_place(|| std::thread::sleep(remaining));
 			}
-			Message::RepeatingMouseClick(ref click) => {
-				if click.amount != 0 && amount_clicked >= click.amount as u128 {
-					continue;
-				}
-
-				if last_click.elapsed().as_millis() >= (click.interval + daemon_settings.mouse.added_delay) as u128 {
-					last_click = std::time::Instant::now();
-					if click.position.0.is_some() || click.position.1.is_some() {
-						if daemon_settings.hyprland_ipc && hypr::is_hyprland() {
-							hypr::move_mouse(mouse.as_ref().unwrap(), click.position.0, click.position.1)?;
-						} else {
-							mouse
-								.as_ref()
-								.unwrap()
-								.move_mouse(click.position.0, click.position.1)?;
-						}
-					}
-					do_mouse_click(&click.button, mouse.as_ref().unwrap())?;
-					if click.typ == "double" {
-						std::thread::sleep(std::time::Duration::from_millis(50));
-						do_mouse_click(&click.button, mouse.as_ref().unwrap())?;
-					}
+		}
 
-					amount_clicked += 1;
-				}
+		let mut done: Vec<u64> = Vec::new();
+		for job in queue.active() {
+			if job.started.is_none() {
+				job.start(&daemon_settings);
+				emit(
+					&events,
+					Message::JobStarted(JobStarted {
+						id: job.id,
+						job: job.job.kind().to_string(),
+					}),
+				);
 			}
-			Message::RepeatingKeyboardClick(ref click) => {
-				if click.amount != 0 && amount_clicked >= click.amount as u128 {
-					continue;
-				}
 
-				if current_action == 0 && last_repeat.elapsed().as_millis() < click.delay_before_repeat as u128 {
-					continue;
-				}
-
-				if in_press_and_release && last_click.elapsed().as_millis() >= click.hold_duration as u128 {
-					if let Actions::PressAndRelease(action) = &click.buttons[current_action] {
-						let key = action.parse().unwrap();
-						keyboard.as_ref().unwrap().release_keyboard_button(key)?;
-						let pos = held_keys.iter().position(|&x| x == key);
-						held_keys.swap_remove(pos.unwrap());
-					}
-					in_press_and_release = false;
-					current_action += 1;
-					if current_action == click.buttons.len() {
-						last_repeat = std::time::Instant::now();
-						amount_clicked += 1;
-						current_action = 0;
-					}
-					last_click = std::time::Instant::now();
-					continue;
-				} else if in_press_and_release {
-					continue;
-				}
+			if job.next_due > std::time::Instant::now() {
+				continue;
+			}
 
-				if let Some(delay) = delay_ms {
-					if last_click.elapsed().as_millis() >= delay as u128 {
-						delay_ms = None;
-					}
-					continue;
-				}
+			let clicked_before = job.clicked;
+			if let Err(e) = job.step(mouse.as_ref(), keyboard.as_ref(), &daemon_settings) {
+				error!("job failed: {e}");
+				emit(
+					&events,
+					Message::JobFailed(JobFailed {
+						id: job.id,
+						job: job.job.kind().to_string(),
+						msg: e.to_string(),
+					}),
+				);
+				done.push(job.id);
+				continue;
+			}
 
-				if last_click.elapsed().as_millis() >= (click.interval + daemon_settings.keyboard.added_delay) as u128 {
-					match &click.buttons[current_action] {
-						Actions::PressAndRelease(action) => {
-							if let Ok(key) = action.parse() {
-								keyboard.as_ref().unwrap().press_keyboard_button(key)?;
-								held_keys.push(key);
-								in_press_and_release = true;
-							} else {
-								warn!("invalid keycode: {action}");
-								continue 'outer;
-							}
-						}
-						Actions::Press(key) => {
-							if let Ok(key) = key.parse() {
-								keyboard.as_ref().unwrap().press_keyboard_button(key)?;
-								held_keys.push(key);
-							} else {
-								warn!("invalid keycode: {key}");
-								continue 'outer;
-							}
-						}
-						Actions::Release(key) => {
-							if let Ok(key) = key.parse() {
-								keyboard.as_ref().unwrap().release_keyboard_button(key)?;
-								let pos = held_keys.iter().position(|&x| x == key);
-								held_keys.swap_remove(pos.unwrap());
-							} else {
-								warn!("invalid keycode: {key}");
-								continue 'outer;
-							}
-						}
-						Actions::Delay(delay) => {
-							delay_ms = Some(*delay);
-						}
-					}
+			if job.is_done() {
+				job_finished(&events, job);
+				done.push(job.id);
+			} else if job.clicked != clicked_before && job.last_progress.elapsed() >= progress_interval {
+				job.last_progress = std::time::Instant::now();
+				emit(
+					&events,
+					Message::JobProgress(JobProgress {
+						id: job.id,
+						job: job.job.kind().to_string(),
+						clicked: job.clicked,
+						amount: job.job.amount(),
+					}),
+				);
+			}
+		}
 
-					if !in_press_and_release {
-						current_action += 1;
-						if current_action == click.buttons.len() {
-							last_repeat = std::time::Instant::now();
-							amount_clicked += 1;
-							current_action = 0;
-						}
-						last_click = std::time::Instant::now();
-					}
-				}
+		for id in done {
+			let mut job = queue.remove(id).unwrap();
+			job.log_jitter();
+			if let Err(e) = job.release(mouse.as_ref(), keyboard.as_ref()) {
+				error!("could not release keys of job {id}: {e}");
 			}
-			_ => todo!(),
+			last_status = job.status();
+			last_status.running = false;
 		}
 	}
 }
@@ -298,7 +449,7 @@ async fn main() -> anyhow::Result<std::process::ExitCode> {
 
 	let mouse = if !settings().lock().unwrap().daemon.mouse.disabled {
 		trace!("creating virtual mouse");
-		Some(Mouse::new().context("could not create virtual mouse")?)
+		Some(Mouse::new(&settings().lock().unwrap().daemon.mouse).context("could not create virtual mouse")?)
 	} else {
 		None
 	};
@@ -310,12 +461,22 @@ async fn main() -> anyhow::Result<std::process::ExitCode> {
 		None
 	};
 
-	let (tx, mut rx) = mpsc::channel::<Message>(64);
+	let (tx, mut rx) = mpsc::channel::<Request>(64);
+
+	let emergency_stop = settings().lock().unwrap().daemon.emergency_stop.clone();
+	if emergency_stop.disabled {
+		debug!("emergency stop is disabled");
+	} else if let Err(e) = emergency::watch(&emergency_stop, tx.clone()) {
+		warn!("emergency stop is not available: {e:#}");
+	}
+
+	let (events, _) = broadcast::channel::<Message>(64);
+	let bg_events = events.clone();
 	let exiting = Arc::new(Notify::new());
 	let clone = exiting.clone();
 	let thread = tokio::spawn(async move {
 		if !settings().lock().unwrap().daemon.dry_run && !args.dry_run {
-			if let Err(e) = bg_thread(clone, rx, mouse, keyboard).await {
+			if let Err(e) = bg_thread(clone, rx, bg_events, mouse, keyboard).await {
 				error!("from bg_thread: {e}");
 			}
 		} else {
@@ -324,7 +485,16 @@ async fn main() -> anyhow::Result<std::process::ExitCode> {
 				tokio::select! {
 					biased;
 					_ = clone.notified() => break,
-					_ = rx.recv() => trace!("got msg from channel"),
+					Some(req) = rx.recv() => {
+						trace!("got msg from channel");
+						let _ = req.reply.send(match req.msg {
+							Message::GetStatus(_) => Message::Status(Status::default()),
+							Message::ListJobs(_) => Message::JobList(JobList::default()),
+							Message::EnqueueJob(_) => Message::JobQueued(JobQueued { id: 0 }),
+							Message::StopRecording(_) => Message::Recording(Recording::default()),
+							_ => Message::ConfirmResponse(ConfirmResponse {}),
+						});
+					}
 					_ = tokio::time::sleep(std::time::Duration::from_millis(25)) => {}
 				}
 			}
@@ -336,7 +506,7 @@ async fn main() -> anyhow::Result<std::process::ExitCode> {
 
 	if settings().lock().unwrap().general.communication_method == settings::latest::Methods::DBus {
 		#[cfg(feature = "dbus")]
-		dbus::listen(tx, Arc::new(handle_msg)).await?;
+		dbus::listen(tx, events, Arc::new(handle_msg)).await?;
 
 		#[cfg(not(feature = "dbus"))]
 		{
@@ -346,7 +516,7 @@ async fn main() -> anyhow::Result<std::process::ExitCode> {
 		}
 	} else if settings().lock().unwrap().general.communication_method == settings::latest::Methods::UnixSocket {
 		#[cfg(feature = "socket")]
-		socket::listen(tx, Arc::new(handle_msg)).await?;
+		socket::listen(tx, events, Arc::new(handle_msg)).await?;
 
 		#[cfg(not(feature = "socket"))]
 		{
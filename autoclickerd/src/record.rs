@@ -0,0 +1,202 @@
+use anyhow::{Context, anyhow};
+use evdev_rs::enums::{EV_KEY, EV_REL, EventCode};
+use evdev_rs::{Device, DeviceWrapper, InputEvent, ReadFlag, ReadStatus};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{
+	Arc,
+	atomic::{AtomicBool, AtomicUsize, Ordering},
+	mpsc,
+};
+use std::time::{Duration, Instant};
+use tracing::{debug, trace, warn};
+
+use crate::vmouse::MouseButton;
+use common::prelude::*;
+
+/// How long a device thread sleeps when there are no events, the recorded timestamps come from the kernel so this does not affect timing
+#[allow(non_upper_case_globals)]
+const poll_interval: Duration = Duration::from_millis(10);
+
+/// Recordings stop on their own after this long or this many events, so a client that never sends `StopRecording` can't grow them forever
+#[allow(non_upper_case_globals)]
+const max_recording_duration: Duration = Duration::from_secs(60 * 60);
+#[allow(non_upper_case_globals)]
+const max_recorded_events: usize = 1_000_000;
+
+/// Relative axes the virtual mouse can replay
+pub const RELATIVE_AXES: [EV_REL; 6] = [
+	EV_REL::REL_X,
+	EV_REL::REL_Y,
+	EV_REL::REL_WHEEL,
+	EV_REL::REL_HWHEEL,
+	EV_REL::REL_WHEEL_HI_RES,
+	EV_REL::REL_HWHEEL_HI_RES,
+];
+
+/// Keyboard or mouse at `path`, `None` for other devices and the daemon's own virtual devices
+pub fn physical_device(path: &Path) -> Option<Device> {
+	if !path
+		.file_name()
+		.is_some_and(|name| name.to_string_lossy().starts_with("event"))
+	{
+		return None;
+	}
+
+	let device = match Device::new_from_path(path) {
+		Ok(o) => o,
+		Err(e) => {
+			debug!("could not open {}: {e}", path.display());
+			return None;
+		}
+	};
+
+	if device
+		.name()
+		.is_some_and(|name| name.starts_with("autoclicker virtual"))
+	{
+		return None;
+	}
+
+	if device.has(EventCode::EV_KEY(EV_KEY::KEY_A)) || device.has(EventCode::EV_KEY(EV_KEY::BTN_LEFT)) {
+		trace!(msg = "found input device", path = %path.display(), name = ?device.name());
+		return Some(device);
+	}
+
+	return None;
+}
+
+/// Keyboards and mice in `/dev/input` with their paths, without the daemon's own virtual devices
+pub fn physical_devices() -> anyhow::Result<Vec<(PathBuf, Device)>> {
+	let mut devices = Vec::new();
+	for entry in std::fs::read_dir("/dev/input").context("could not read /dev/input")? {
+		let path = entry.context("could not read /dev/input")?.path();
+		if let Some(device) = physical_device(&path) {
+			devices.push((path, device));
+		}
+	}
+
+	return Ok(devices);
+}
+
+fn is_replayable(event: &InputEvent) -> bool {
+	return match event.event_code {
+		EventCode::EV_KEY(key) => (event.value == 0 || event.value == 1) && (MouseButton::from_key(key).is_some() || event.event_code.to_string().starts_with("KEY_")),
+		EventCode::EV_REL(axis) => RELATIVE_AXES.contains(&axis),
+		_ => false,
+	};
+}
+
+/// Reads events from every physical keyboard and mouse until stopped
+pub struct Recorder {
+	stop: Arc<AtomicBool>,
+	threads: Vec<std::thread::JoinHandle<()>>,
+	rx: mpsc::Receiver<(u64, String, i32)>,
+}
+
+impl Recorder {
+	pub fn start() -> anyhow::Result<Self> {
+		let devices = physical_devices()?;
+		if devices.is_empty() {
+			return Err(anyhow!("no keyboard or mouse could be opened in /dev/input, make sure the user is in the input group"));
+		}
+
+		let stop = Arc::new(AtomicBool::new(false));
+		let recorded = Arc::new(AtomicUsize::new(0));
+		let started = Instant::now();
+		let (tx, rx) = mpsc::channel();
+		let threads = devices
+			.into_iter()
+			.map(|(_, device)| {
+				let stop = stop.clone();
+				let recorded = recorded.clone();
+				let tx = tx.clone();
+				std::thread::spawn(move || {
+					while !stop.load(Ordering::Relaxed) {
+						if started.elapsed() >= max_recording_duration {
+							warn!("stopped recording after {}s", max_recording_duration.as_secs());
+							stop.store(true, Ordering::Relaxed);
+							return;
+						}
+
+						match device.next_event(ReadFlag::NORMAL) {
+							Ok((ReadStatus::Success, event)) if is_replayable(&event) => {
+								if recorded.fetch_add(1, Ordering::Relaxed) >= max_recorded_events {
+									warn!("stopped recording after {max_recorded_events} events");
+									stop.store(true, Ordering::Relaxed);
+									return;
+								}
+
+								let time = event.time.tv_sec as u64 * 1_000_000 + event.time.tv_usec as u64;
+								let _ = tx.send((time, event.event_code.to_string(), event.value));
+							}
+							Ok(_) => {}
+							Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => std::thread::sleep(poll_interval),
+							Err(e) => {
+								warn!("stopped recording {:?}: {e}", device.name());
+								return;
+							}
+						}
+					}
+				})
+			})
+			.collect();
+
+		return Ok(Self { stop, threads, rx });
+	}
+
+	/// Whether the recording hit its limits and stopped reading on its own
+	pub fn is_stopped(&self) -> bool {
+		return self.stop.load(Ordering::Relaxed);
+	}
+
+	/// Stops reading and returns everything recorded, with the time counted from the first event
+	pub fn stop(self) -> Vec<MacroEvent> {
+		self.stop.store(true, Ordering::Relaxed);
+		for thread in self.threads {
+			let _ = thread.join();
+		}
+
+		let mut events: Vec<(u64, String, i32)> = self.rx.try_iter().collect();
+		events.sort_by_key(|e| e.0);
+		let recorded = events.len();
+
+		// drop releases without a recorded press, like the key that started the recording
+		let mut held: HashSet<String> = HashSet::new();
+		events.retain(|(_, code, value)| match (code.starts_with("REL_"), value) {
+			(true, _) => true,
+			(false, 1) => {
+				held.insert(code.clone());
+				true
+			}
+			(false, _) => held.remove(code),
+		});
+
+		// and presses that are never released, like the key that stopped it
+		let mut released: HashSet<String> = HashSet::new();
+		let mut kept: Vec<(u64, String, i32)> = events
+			.into_iter()
+			.rev()
+			.filter(|(_, code, value)| match (code.starts_with("REL_"), value) {
+				(true, _) => true,
+				(false, 0) => {
+					released.insert(code.clone());
+					true
+				}
+				(false, _) => released.remove(code),
+			})
+			.collect();
+		kept.reverse();
+		debug!(recorded, kept = kept.len());
+
+		let start = kept.first().map(|e| e.0).unwrap_or(0);
+		return kept
+			.into_iter()
+			.map(|(time, code, value)| MacroEvent {
+				time: (time - start) / 1000,
+				code,
+				value,
+			})
+			.collect();
+	}
+}
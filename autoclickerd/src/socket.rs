@@ -1,13 +1,13 @@
-use super::settings;
+use super::{Request, settings};
 use anyhow::Context;
 use std::sync::Arc;
 use tokio::{
-	io::{AsyncReadExt, AsyncWriteExt},
+	io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
 	net::{UnixListener, UnixStream},
 	signal::unix::{SignalKind, signal},
-	sync::mpsc::Sender,
+	sync::{broadcast, mpsc::Sender, oneshot},
 };
-use tracing::{error, info, trace};
+use tracing::{debug, error, info, trace};
 
 use common::prelude::*;
 
@@ -26,7 +26,54 @@ fn socket_file() -> String {
 		.replace("$id", id.to_string().as_str());
 }
 
-pub async fn listen<F, O>(tx: Sender<Message>, handle_msg: Arc<F>) -> anyhow::Result<()>
+async fn write_line(stream: &mut UnixStream, message: &Message) -> anyhow::Result<()> {
+	let mut json = Message::encode(message)?;
+	json.push('\n');
+	stream
+		.write_all(json.as_bytes())
+		.await
+		.context("could not write to stream")?;
+	return Ok(());
+}
+
+async fn subscribe(mut stream: UnixStream, tx: Sender<Request>, mut events: broadcast::Receiver<Message>) -> anyhow::Result<()> {
+	let (reply, response) = oneshot::channel();
+	tx.send(Request {
+		msg: Message::GetStatus(GetStatus {}),
+		reply,
+	})
+	.await
+	.context("could not send event over channel")?;
+	let status = response
+		.await
+		.context("background thread did not respond")?;
+	write_line(&mut stream, &status).await?;
+
+	trace!("client subscribed");
+	let mut buf = [0u8; 64];
+	loop {
+		tokio::select! {
+			event = events.recv() => {
+				match event {
+					Ok(event) => write_line(&mut stream, &event).await?,
+					Err(broadcast::error::RecvError::Lagged(n)) => debug!("subscriber lagged behind by {n} events"),
+					Err(broadcast::error::RecvError::Closed) => break,
+				}
+			}
+			read = stream.read(&mut buf) => {
+				// anything sent after subscribing is ignored, eof means the client went away
+				if read.unwrap_or(0) == 0 {
+					break;
+				}
+			}
+		}
+	}
+	trace!("client unsubscribed");
+
+	return Ok(());
+}
+
+pub async fn listen<F, O>(tx: Sender<Request>, events: broadcast::Sender<Message>, handle_msg: Arc<F>) -> anyhow::Result<()>
 where
 	F: Fn(String) -> O + Send + Sync + 'static,
 	O: Future<Output = anyhow::Result<Message>> + Send,
@@ -44,19 +91,33 @@ where
 	};
 	trace!("binded");
 
-	let func = async |mut stream: UnixStream, tx: Sender<Message>, handler: Arc<F>| -> anyhow::Result<()> {
+	let func = async |stream: UnixStream, tx: Sender<Request>, events: broadcast::Sender<Message>, handler: Arc<F>| -> anyhow::Result<()> {
+		// requests end with either a newline or eof, only a complete line is treated as a request on its own
+		let mut reader = BufReader::new(stream);
 		let mut msg = String::new();
-		stream
-			.read_to_string(&mut msg)
+		reader
+			.read_line(&mut msg)
 			.await
 			.context("failed to read stream")?;
+		if msg.ends_with('\n') && matches!(Message::decode(msg.as_str()), Ok(Message::Subscribe(_))) {
+			return subscribe(reader.into_inner(), tx, events.subscribe()).await;
+		} else if Message::decode(msg.as_str()).is_err() {
+			reader
+				.read_to_string(&mut msg)
+				.await
+				.context("failed to read stream")?;
+		}
+		let mut stream = reader.into_inner();
 
 		match handler(msg).await {
 			Ok(o) => {
-				tx.send(o)
+				let (reply, response) = oneshot::channel();
+				tx.send(Request { msg: o, reply })
 					.await
 					.context("could not send event over channel")?;
-				let message = Message::ConfirmResponse(ConfirmResponse {});
+				let message = response
+					.await
+					.context("background thread did not respond")?;
 				let json = Message::encode(&message)?;
 				stream
 					.write_all(json.as_bytes())
@@ -93,7 +154,8 @@ where
 			}
 			Ok((stream, _)) = listener.accept() => {
 				let tx = tx.clone();
-				tokio::spawn(func(stream, tx, handle_msg.clone()));
+				let events = events.clone();
+				tokio::spawn(func(stream, tx, events, handle_msg.clone()));
 			}
 		}
 	}
@@ -1,38 +1,180 @@
 use anyhow::Context;
-use evdev_rs::enums::{BusType, EV_KEY, EV_REL, EV_SYN, EventCode};
-use evdev_rs::{DeviceWrapper, UInputDevice, UninitDevice};
+use evdev_rs::enums::{BusType, EV_ABS, EV_KEY, EV_REL, EV_SYN, EventCode};
+use evdev_rs::{AbsInfo, DeviceWrapper, EnableCodeData, UInputDevice, UninitDevice};
+use std::cell::Cell;
+use tracing::info;
 
 use crate::vdevice::*;
+use common::settings::latest::{MouseSettings, PointerDevice};
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum MouseButton {
 	Left,
 	Right,
 	Middle,
+	Side,
+	Extra,
+	Forward,
+	Back,
+	Task,
+}
+
+impl MouseButton {
+	const ALL: [MouseButton; 8] = [
+		MouseButton::Left,
+		MouseButton::Right,
+		MouseButton::Middle,
+		MouseButton::Side,
+		MouseButton::Extra,
+		MouseButton::Forward,
+		MouseButton::Back,
+		MouseButton::Task,
+	];
+
+	pub fn from_key(key: EV_KEY) -> Option<Self> {
+		return Self::ALL.into_iter().find(|button| button.key() == key);
+	}
+
+	fn key(&self) -> EV_KEY {
+		return match self {
+			MouseButton::Left => EV_KEY::BTN_LEFT,
+			MouseButton::Right => EV_KEY::BTN_RIGHT,
+			MouseButton::Middle => EV_KEY::BTN_MIDDLE,
+			MouseButton::Side => EV_KEY::BTN_SIDE,
+			MouseButton::Extra => EV_KEY::BTN_EXTRA,
+			MouseButton::Forward => EV_KEY::BTN_FORWARD,
+			MouseButton::Back => EV_KEY::BTN_BACK,
+			MouseButton::Task => EV_KEY::BTN_TASK,
+		};
+	}
+}
+
+impl std::str::FromStr for MouseButton {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		return Ok(match s {
+			"left" => Self::Left,
+			"right" => Self::Right,
+			"middle" => Self::Middle,
+			"side" => Self::Side,
+			"extra" => Self::Extra,
+			"forward" => Self::Forward,
+			"back" => Self::Back,
+			"task" => Self::Task,
+			_ => return Err(anyhow::anyhow!("invalid mouse button: {s}")),
+		});
+	}
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum ScrollAxis {
+	Vertical,
+	Horizontal,
+}
+
+impl ScrollAxis {
+	fn codes(&self) -> (EV_REL, EV_REL) {
+		return match self {
+			ScrollAxis::Vertical => (EV_REL::REL_WHEEL, EV_REL::REL_WHEEL_HI_RES),
+			ScrollAxis::Horizontal => (EV_REL::REL_HWHEEL, EV_REL::REL_HWHEEL_HI_RES),
+		};
+	}
+}
+
+impl std::str::FromStr for ScrollAxis {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		return Ok(match s {
+			"vertical" => Self::Vertical,
+			"horizontal" => Self::Horizontal,
+			_ => return Err(anyhow::anyhow!("invalid scroll axis: {s}")),
+		});
+	}
+}
+
+/// Tablet-like device that puts the cursor exactly on a position, regardless of pointer acceleration or monitor layout
+#[allow(unused)]
+struct AbsolutePointer {
+	pointer: UninitDevice,
+	input: UInputDevice,
+}
+
+impl AbsolutePointer {
+	fn new(screen_size: (u32, u32)) -> anyhow::Result<Self> {
+		let pointer = UninitDevice::new().context("could not create absolute pointer")?;
+		pointer.set_name("autoclicker virtual absolute pointer");
+		pointer.set_bustype(BusType::BUS_USB as u16);
+		pointer.set_vendor_id(0xabcd);
+		pointer.set_product_id(0xefee);
+
+		// without a button, udev does not tag the device as a mouse and compositors ignore it
+		pointer
+			.enable(EventCode::EV_KEY(EV_KEY::BTN_LEFT))
+			.context("could not enable left mouse key")?;
+
+		for (code, size) in [(EV_ABS::ABS_X, screen_size.0), (EV_ABS::ABS_Y, screen_size.1)] {
+			let info = AbsInfo {
+				value: 0,
+				minimum: 0,
+				maximum: size.saturating_sub(1).min(i32::MAX as u32) as i32,
+				fuzz: 0,
+				flat: 0,
+				resolution: 0,
+			};
+			pointer
+				.enable_event_code(&EventCode::EV_ABS(code), Some(EnableCodeData::AbsInfo(info)))
+				.with_context(|| format!("could not enable {code:?}"))?;
+		}
+
+		pointer
+			.enable(EventCode::EV_SYN(EV_SYN::SYN_REPORT))
+			.context("could not enable SYN_REPORT")?;
+
+		let input = UInputDevice::create_from_device(&pointer).context("could not create input device")?;
+
+		return Ok(Self { pointer, input });
+	}
+
+	/// Both axes are always sent, an axis left out would stay where this device last put it, not where the cursor is
+	fn move_pointer(&self, x: i32, y: i32) -> anyhow::Result<()> {
+		self.send_event(EventCode::EV_ABS(EV_ABS::ABS_X), x)?;
+		self.send_event(EventCode::EV_ABS(EV_ABS::ABS_Y), y)?;
+		self.send_sync()?;
+
+		return Ok(());
+	}
+}
+
+impl VirtualDevice for AbsolutePointer {
+	fn get_input(&self) -> &UInputDevice {
+		return &self.input;
+	}
 }
 
 #[allow(unused)]
 pub struct Mouse {
 	mouse: UninitDevice,
 	input: UInputDevice,
+	absolute: Option<AbsolutePointer>,
+	/// Where the cursor was last put, `None` until both axes have been set once
+	last_position: Cell<Option<(i32, i32)>>,
 }
 
 impl Mouse {
-	pub fn new() -> anyhow::Result<Self> {
+	pub fn new(settings: &MouseSettings) -> anyhow::Result<Self> {
 		let mouse = UninitDevice::new().context("could not create mouse")?;
 		mouse.set_name("autoclicker virtual mouse");
 		mouse.set_bustype(BusType::BUS_USB as u16);
 		mouse.set_vendor_id(0xabcd);
 		mouse.set_product_id(0xefef);
 
-		mouse
-			.enable(EventCode::EV_KEY(EV_KEY::BTN_LEFT))
-			.context("could not enable left mouse key")?;
-		mouse
-			.enable(EventCode::EV_KEY(EV_KEY::BTN_MIDDLE))
-			.context("could not enable middle mouse key")?;
-		mouse
-			.enable(EventCode::EV_KEY(EV_KEY::BTN_RIGHT))
-			.context("could not enable right mouse key")?;
+		for button in MouseButton::ALL {
+			mouse
+				.enable(EventCode::EV_KEY(button.key()))
+				.with_context(|| format!("could not enable {button:?} mouse key"))?;
+		}
 
 		mouse
 			.enable(EventCode::EV_REL(EV_REL::REL_X))
@@ -40,6 +182,11 @@ impl Mouse {
 		mouse
 			.enable(EventCode::EV_REL(EV_REL::REL_Y))
 			.context("could not enable rel_y")?;
+		for code in [EV_REL::REL_WHEEL, EV_REL::REL_HWHEEL, EV_REL::REL_WHEEL_HI_RES, EV_REL::REL_HWHEEL_HI_RES] {
+			mouse
+				.enable(EventCode::EV_REL(code))
+				.with_context(|| format!("could not enable {code:?}"))?;
+		}
 
 		mouse
 			.enable(EventCode::EV_SYN(EV_SYN::SYN_REPORT))
@@ -47,7 +194,37 @@ impl Mouse {
 
 		let input = UInputDevice::create_from_device(&mouse).context("could not create input device")?;
 
-		return Ok(Self { mouse, input });
+		let absolute = match settings.pointer {
+			PointerDevice::Relative => None,
+			PointerDevice::Absolute => {
+				info!(
+					"absolute pointer mapped to {}x{}, set `screen_size` if that is not the size of the desktop",
+					settings.screen_size.0, settings.screen_size.1
+				);
+				Some(AbsolutePointer::new(settings.screen_size)?)
+			}
+		};
+
+		return Ok(Self {
+			mouse,
+			input,
+			absolute,
+			last_position: Cell::new(None),
+		});
+	}
+
+	pub fn last_position(&self) -> Option<(i32, i32)> {
+		return self.last_position.get();
+	}
+
+	/// Remembers that the cursor was put on `x` and `y`, an axis that is `None` did not change
+	pub fn track(&self, x: Option<i32>, y: Option<i32>) {
+		self.last_position
+			.set(match (x, y, self.last_position.get()) {
+				(Some(x), Some(y), _) => Some((x, y)),
+				(x, y, Some(last)) => Some((x.unwrap_or(last.0), y.unwrap_or(last.1))),
+				_ => None,
+			});
 	}
 
 	#[inline]
@@ -65,39 +242,57 @@ impl Mouse {
 
 	#[inline]
 	pub fn move_mouse(&self, x: Option<i32>, y: Option<i32>) -> anyhow::Result<()> {
-		self.move_mouse_relative(Some(i32::MIN), Some(i32::MIN))?;
+		if let Some(absolute) = &self.absolute {
+			match (x, y, self.last_position.get()) {
+				(Some(x), Some(y), _) => return absolute.move_pointer(x, y),
+				(x, y, Some(last)) => return absolute.move_pointer(x.unwrap_or(last.0), y.unwrap_or(last.1)),
+				// the other axis is unknown, the relative device can move one axis on its own
+				_ => {}
+			}
+		}
+
+		self.move_mouse_relative(x.map(|_| i32::MIN), y.map(|_| i32::MIN))?;
 		self.move_mouse_relative(x, y)?;
 
 		return Ok(());
 	}
 
+	/// Sends a single relative event, e.g. one recorded from a physical mouse
 	#[inline]
-	pub fn click_mouse_button(&self, button: MouseButton) -> anyhow::Result<()> {
-		match button {
-			MouseButton::Left => {
-				self.send_event(EventCode::EV_KEY(EV_KEY::BTN_LEFT), 1)?;
-				self.send_sync()?;
-
-				self.send_event(EventCode::EV_KEY(EV_KEY::BTN_LEFT), 0)?;
-				self.send_sync()?;
-			}
+	pub fn move_relative_axis(&self, axis: EV_REL, value: i32) -> anyhow::Result<()> {
+		self.send_event(EventCode::EV_REL(axis), value)?;
+		self.send_sync()?;
 
-			MouseButton::Right => {
-				self.send_event(EventCode::EV_KEY(EV_KEY::BTN_RIGHT), 1)?;
-				self.send_sync()?;
+		return Ok(());
+	}
 
-				self.send_event(EventCode::EV_KEY(EV_KEY::BTN_RIGHT), 0)?;
-				self.send_sync()?;
-			}
+	/// Sends `notches` whole wheel clicks along with `hi_res` 1/120 notches, the way a high-resolution wheel reports both
+	#[inline]
+	pub fn scroll(&self, axis: ScrollAxis, notches: i32, hi_res: i32) -> anyhow::Result<()> {
+		let (wheel, wheel_hi_res) = axis.codes();
+		if notches != 0 {
+			self.send_event(EventCode::EV_REL(wheel), notches)?;
+		}
+		if hi_res != 0 {
+			self.send_event(EventCode::EV_REL(wheel_hi_res), hi_res)?;
+		}
+		self.send_sync()?;
+
+		return Ok(());
+	}
 
-			MouseButton::Middle => {
-				self.send_event(EventCode::EV_KEY(EV_KEY::BTN_MIDDLE), 1)?;
-				self.send_sync()?;
+	#[inline]
+	pub fn press_mouse_button(&self, button: MouseButton) -> anyhow::Result<()> {
+		self.send_event(EventCode::EV_KEY(button.key()), 1)?;
+		self.send_sync()?;
 
-				self.send_event(EventCode::EV_KEY(EV_KEY::BTN_MIDDLE), 0)?;
-				self.send_sync()?;
-			}
-		}
+		return Ok(());
+	}
+
+	#[inline]
+	pub fn release_mouse_button(&self, button: MouseButton) -> anyhow::Result<()> {
+		self.send_event(EventCode::EV_KEY(button.key()), 0)?;
+		self.send_sync()?;
 
 		return Ok(());
 	}
@@ -6,4 +6,23 @@ pub enum Actions {
 	Press(String),
 	Release(String),
 	Delay(i64),
+	/// Presses and releases a mouse button, like `left`
+	Click(String),
+	MouseDown(String),
+	MouseUp(String),
+	/// Moves the cursor to a position on the screen
+	Move(i32, i32),
+	MoveRelative(i32, i32),
+	/// Scrolls vertically by whole notches, positive is up
+	Scroll(i32),
+}
+
+impl Actions {
+	/// Runs on the virtual mouse instead of the keyboard
+	pub fn is_mouse(&self) -> bool {
+		return matches!(
+			self,
+			Self::Click(_) | Self::MouseDown(_) | Self::MouseUp(_) | Self::Move(..) | Self::MoveRelative(..) | Self::Scroll(_)
+		);
+	}
 }
@@ -0,0 +1,610 @@
+use nom::{
+	IResult, Offset, Parser,
+	branch::alt,
+	bytes::complete::{tag, take_while1},
+	character::complete::{alpha1, alphanumeric1, char, multispace0, multispace1, one_of},
+	combinator::{cut, map, opt, recognize},
+	error::{ParseError, context},
+	multi::{many0, many1, separated_list0, separated_list1},
+	sequence::{delimited, pair, preceded, terminated},
+};
+use nom_language::error::{VerboseError, VerboseErrorKind};
+
+use crate::actions::Actions;
+use crate::keycodes;
+use crate::xkb::Layout;
+use anyhow::anyhow;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::{Arc, RwLock};
+use strings::Part;
+use tracing::{error, info, trace};
+
+pub mod strings;
+mod tests;
+
+type ParseResult<'a, I, O> = IResult<I, O, VerboseError<&'a str>>;
+
+/// Values of the variables a sequence uses without defining them, by name
+pub type Parameters = HashMap<String, String>;
+
+#[derive(Debug, PartialEq)]
+enum Literal {
+	String(String),
+	Number(i64),
+}
+
+#[derive(Debug)]
+enum Token<'a> {
+	Sequence(Vec<Part>),
+	Key(String),
+	/// Keys held down together, like `ctrl+shift+t` or `<C-S-t>`
+	Chord(Vec<String>),
+	/// `let name = "..."`
+	Let((String, Vec<Part>)),
+	Action((String, Vec<Literal>)),
+	/// Start of a `repeat(n) { ... }` block
+	Repeat(i64),
+	BlockEnd,
+	Unknown(&'a str),
+}
+
+static LAYOUT: RwLock<Option<Arc<Layout>>> = RwLock::new(None);
+
+/// Sets the keyboard layout strings are typed with from now on, replacing the one set before. Until then the US layout is used
+pub fn set_layout(layout: Layout) {
+	info!("typing strings with the {} keyboard layout", layout.name());
+	*LAYOUT.write().unwrap() = Some(Arc::new(layout));
+}
+
+fn layout() -> Arc<Layout> {
+	if let Some(layout) = LAYOUT.read().unwrap().as_ref() {
+		return layout.clone();
+	}
+
+	return LAYOUT
+		.write()
+		.unwrap()
+		.get_or_insert_with(|| Arc::new(Layout::us()))
+		.clone();
+}
+
+/// Buttons of `click`, `mousedown` and `mouseup`
+#[allow(non_upper_case_globals)]
+const mouse_buttons: [&str; 8] = ["left", "right", "middle", "side", "extra", "forward", "back", "task"];
+
+/// Upper limit of actions after expanding repeat blocks, so a typo like `repeat(1000000)` can't use up all memory
+#[allow(non_upper_case_globals)]
+const max_actions: usize = 100_000;
+
+fn parse_ident(input: &str) -> ParseResult<'_, &str, String> {
+	let res = recognize(pair(alt((alpha1, tag("_"))), many0(alt((alphanumeric1, tag("_")))))).parse(input)?;
+
+	return Ok((res.0, res.1.into()));
+}
+
+fn parse_number(input: &str) -> ParseResult<'_, &str, i64> {
+	let res = recognize(preceded(opt(char('-')), many1(terminated(one_of("0123456789"), many0(char('_')))))).parse(input)?;
+
+	let num: i64 = match res.1.parse() {
+		Ok(o) => o,
+		Err(_) => {
+			let mut err = VerboseError::from_error_kind(res.1, nom::error::ErrorKind::Fail);
+			err.errors
+				.push((res.1, VerboseErrorKind::Context("invalid i64")));
+			return Err(nom::Err::Error(err));
+		}
+	};
+
+	return Ok((res.0, num));
+}
+
+fn func(input: &str) -> ParseResult<'_, &str, (String, Vec<Literal>)> {
+	let res = recognize(pair(alt((alpha1, tag("_"))), many0(alt((alphanumeric1, tag("_")))))).parse(input)?;
+
+	let ident = res.1;
+	// `move` is also a key, it is only a function when called
+	if !["press", "release", "delay", "click", "mousedown", "mouseup", "move", "moverel", "scroll"].contains(&ident) || (!res.0.starts_with('(') && keycodes::key_exists(ident)) {
+		let mut err = VerboseError::from_error_kind(res.1, nom::error::ErrorKind::Fail);
+		err.errors
+			.push((res.1, VerboseErrorKind::Context("unknown function")));
+		return Err(nom::Err::Error(err));
+	}
+
+	let res = cut(delimited(
+		context("expected (", char('(')),
+		separated_list0(
+			char(','),
+			delimited(multispace0, alt((map(parse_ident, Literal::String), map(parse_number, Literal::Number))), multispace0),
+		),
+		context("expected ')'", char(')')),
+	))
+	.parse(res.0)?;
+
+	return Ok((res.0, (ident.to_string(), res.1)));
+}
+
+fn repeat(input: &str) -> ParseResult<'_, &str, i64> {
+	let res = pair(tag("repeat"), preceded(multispace0, char('('))).parse(input)?;
+
+	return cut(terminated(
+		delimited(multispace0, context("expected a number", parse_number), multispace0),
+		pair(context("expected ')'", char(')')), preceded(multispace0, context("expected '{'", char('{')))),
+	))
+	.parse(res.0);
+}
+
+fn binding(input: &str) -> ParseResult<'_, &str, (String, Vec<Part>)> {
+	let res = terminated(tag("let"), multispace1).parse(input)?;
+
+	return cut(pair(
+		terminated(context("expected a variable name", parse_ident), multispace0),
+		preceded(pair(context("expected '='", char('=')), multispace0), context("expected a string", strings::parse_string)),
+	))
+	.parse(res.0);
+}
+
+fn key_name(input: &str) -> ParseResult<'_, &str, &str> {
+	return recognize(pair(alt((alphanumeric1, tag("_"))), many0(alt((alphanumeric1, tag("_")))))).parse(input);
+}
+
+/// Key of a modifier alias in chords, the single letters are only used in the `<C-S-t>` form so `a+b` still means the keys
+fn modifier(name: &str, short: bool) -> Option<&'static str> {
+	return match (name.to_lowercase().as_str(), short) {
+		("ctrl" | "control", _) | ("c", true) => Some("LEFTCTRL"),
+		("shift", _) | ("s", true) => Some("LEFTSHIFT"),
+		("alt", _) | ("a" | "m", true) => Some("LEFTALT"),
+		("altgr", _) => Some("RIGHTALT"),
+		("super" | "meta" | "win", _) | ("d", true) => Some("LEFTMETA"),
+		_ => None,
+	};
+}
+
+fn chord(input: &str) -> ParseResult<'_, &str, Vec<String>> {
+	let res = alt((
+		map(
+			preceded(
+				char('<'),
+				cut(terminated(
+					separated_list1(char('-'), context("expected a key", key_name)),
+					context("expected '>'", char('>')),
+				)),
+			),
+			|names| (names, true),
+		),
+		map(
+			pair(key_name, preceded(char('+'), cut(separated_list1(char('+'), context("expected a key", key_name))))),
+			|(first, mut names)| {
+				names.insert(0, first);
+				return (names, false);
+			},
+		),
+	))
+	.parse(input)?;
+
+	let (names, short) = res.1;
+	let mut keys: Vec<String> = Vec::new();
+	for (i, name) in names.iter().enumerate() {
+		// the last key is pressed while the modifiers are held, so a key name wins over an alias there
+		let key = if i == names.len() - 1 && keycodes::key_exists(name) {
+			Some(name.to_string())
+		} else {
+			modifier(name, short)
+				.map(str::to_string)
+				.or_else(|| Some(name.to_string()).filter(|name| keycodes::key_exists(name)))
+		};
+
+		let Some(key) = key else {
+			let mut err = VerboseError::from_error_kind(*name, nom::error::ErrorKind::Fail);
+			err.errors
+				.push((*name, VerboseErrorKind::Context("unknown key or modifier")));
+			return Err(nom::Err::Failure(err));
+		};
+
+		keys.push(format!("KEY_{}", key.to_uppercase()));
+	}
+
+	return Ok((res.0, keys));
+}
+
+fn key(input: &str) -> ParseResult<'_, &str, String> {
+	let res = key_name(input)?;
+
+	if keycodes::key_exists(res.1) {
+		return Ok((res.0, res.1.into()));
+	}
+
+	let mut err = VerboseError::from_error_kind(res.1, nom::error::ErrorKind::Fail);
+	err.errors
+		.push((res.1, VerboseErrorKind::Context("unknown key")));
+	return Err(nom::Err::Error(err));
+}
+
+fn convert_error(input: &String, err: VerboseError<&str>) -> String {
+	let mut result = String::new();
+
+	for (substring, kind) in err.errors.iter() {
+		let offset = input.offset(substring);
+
+		if input.is_empty() {
+			match kind {
+				VerboseErrorKind::Char(c) => write!(&mut result, "error: expected '{c}', got empty input\n\n"),
+				VerboseErrorKind::Context(ctx) => write!(&mut result, "error: in {ctx}, got empty input\n\n"),
+				VerboseErrorKind::Nom(e) => write!(&mut result, "error: in {e:?}, got empty input\n\n"),
+			}
+			.unwrap();
+
+			continue;
+		}
+
+		let prefix = &input.as_bytes()[..offset];
+
+		let line_number = prefix.iter().filter(|&&b| b == b'\n').count() + 1;
+		let line_begin = prefix
+			.iter()
+			.rev()
+			.position(|&b| b == b'\n')
+			.map(|pos| offset - pos)
+			.unwrap_or(0);
+
+		let line = input[line_begin..]
+			.lines()
+			.next()
+			.unwrap_or(&input[line_begin..])
+			.trim_end();
+
+		let column_number = line.offset(substring) + 1;
+
+		match kind {
+			VerboseErrorKind::Char(c) => {
+				if let Some(actual) = substring.chars().next() {
+					write!(&mut result, "error: expected '{c}', found {actual}\nline:column {line_number}:{column_number}\n{line}\n\n")
+				} else {
+					write!(
+						&mut result,
+						"error: expected '{c}', got end of input\nline:column {line_number}:{column_number}\n{line}\n\n"
+					)
+				}
+			}
+			VerboseErrorKind::Context(ctx) => {
+				write!(&mut result, "error: {ctx}\nline:column {line_number}:{column_number}\n{line}\n\n")
+			}
+			VerboseErrorKind::Nom(err) => {
+				write!(&mut result, "error: {err:?}\nline:column {line_number}:{column_number}\n{line}\n\n")
+			} // VerboseErrorKind::Char(c) => {
+			  // 	if let Some(actual) = substring.chars().next() {
+			  // 		write!(&mut result, "error: expected '{c}', found {actual}\n   --> line {line_number}\n     {line}\n{caret:>column$}\n\n", caret = "^", column = column_number + 5)
+			  // 	} else {
+			  // 		write!(&mut result, "error: expected '{c}', got end of input\n   --> line {line_number}\n     {line}\n{caret:>column$}\n\n", caret = "^", column = column_number + 5)
+			  // 	}
+			  // }
+			  // VerboseErrorKind::Context(ctx) => {
+			  // 	write!(&mut result, "error: {ctx}\n   --> line {line_number}\n     {line}\n{caret:>column$}\n\n", caret = "^", column = column_number + 5)
+			  // }
+			  // VerboseErrorKind::Nom(err) => {
+			  // 	write!(&mut result, "error: {err:?}\n   --> line {line_number}\n     {line}\n{caret:>column$}\n\n", caret = "^", column = column_number + 5)
+			  // }
+		}
+		.unwrap();
+	}
+
+	return result;
+}
+
+fn token(input: &str) -> ParseResult<'_, &str, Token<'_>> {
+	return preceded(
+		multispace0,
+		alt((
+			map(strings::parse_string, Token::Sequence),
+			map(binding, Token::Let),
+			map(repeat, Token::Repeat),
+			map(char('}'), |_| Token::BlockEnd),
+			map(chord, Token::Chord),
+			map(func, Token::Action),
+			map(key, Token::Key),
+			map(take_while1(|c: char| !c.is_whitespace()), Token::Unknown),
+		)),
+	)
+	.parse(input);
+}
+
+/// Joins the parts of a string literal, with the variables defined so far
+fn fill(parts: Vec<Part>, variables: &HashMap<String, String>) -> anyhow::Result<String> {
+	let mut s = String::new();
+	for part in parts {
+		match part {
+			Part::Text(text) => s.push_str(&text),
+			Part::Variable(name) => match variables.get(&name) {
+				Some(value) => s.push_str(value),
+				None => {
+					error!("undefined variable: {name}");
+					return Err(anyhow!("undefined variable: {name}, define it with `let {name} = \"...\"` or pass it as a parameter"));
+				}
+			},
+		}
+	}
+
+	return Ok(s);
+}
+
+/// Parses tokens until the end of the input, or the `}` closing the block at `depth`
+fn parse_block(input: &String, rest: &mut &str, depth: usize, variables: &mut HashMap<String, String>) -> anyhow::Result<Vec<Actions>> {
+	let mut actions: Vec<Actions> = Vec::new();
+
+	loop {
+		if rest.chars().all(|c| c.is_whitespace()) {
+			if depth > 0 {
+				error!("unclosed repeat block");
+				return Err(anyhow!("expected '}}' to close the repeat block"));
+			}
+			break;
+		}
+
+		let res = token(rest).map_err(|e| {
+			match e {
+				nom::Err::Error(ref e) | nom::Err::Failure(ref e) => {
+					let s = convert_error(input, e.clone());
+					error!(s);
+					return anyhow!("{s}");
+				}
+				_ => {}
+			}
+
+			error!(?e);
+			return anyhow!("{e}").context("parse error");
+		})?;
+
+		*rest = res.0;
+
+		trace!(token = ?res.1);
+
+		match res.1 {
+			Token::Repeat(count) => {
+				let block = parse_block(input, rest, depth + 1, variables)?;
+				if count < 0 {
+					return Err(anyhow!("repeat count must not be negative: {count}"));
+				}
+
+				if block.len().saturating_mul(count as usize) > max_actions.saturating_sub(actions.len()) {
+					return Err(anyhow!("sequence is longer than {max_actions} actions after repeating"));
+				}
+
+				// `cycle` over an empty block ends immediately, however large `count` is
+				actions.extend(
+					block
+						.iter()
+						.cloned()
+						.cycle()
+						.take(block.len() * count as usize),
+				);
+			}
+			Token::BlockEnd => {
+				if depth == 0 {
+					error!("unmatched '}}'");
+					return Err(anyhow!("unmatched '}}'"));
+				}
+				return Ok(actions);
+			}
+			Token::Unknown(token) => {
+				error!("unknown token: {token}");
+				return Err(anyhow!("unknown token: {token}"));
+			}
+			Token::Let((name, value)) => {
+				let value = fill(value, variables)?;
+				variables.insert(name, value);
+			}
+			Token::Sequence(seq) => {
+				let seq = fill(seq, variables)?;
+				actions.extend(layout().type_str(&seq)?);
+			}
+			Token::Chord(keys) => {
+				for key in keys.iter() {
+					actions.push(Actions::Press(key.clone()));
+				}
+				for key in keys.into_iter().rev() {
+					actions.push(Actions::Release(key));
+				}
+			}
+			Token::Key(kw) => {
+				let mut s = String::new();
+				s.push_str("KEY_");
+				s.push_str(&kw.to_uppercase());
+				actions.push(Actions::PressAndRelease(s));
+			}
+			Token::Action(action) => {
+				let args = action.1;
+				match action.0.as_str() {
+					"delay" => match args.as_slice() {
+						[Literal::Number(num)] => {
+							actions.push(Actions::Delay(*num));
+						}
+						_ => return Err(anyhow!("delay is defined as: `delay(number)`")),
+					},
+					"press" => match args.as_slice() {
+						[Literal::String(key)] => {
+							if !keycodes::key_exists(key) {
+								return Err(anyhow!("invalid key: {key}"));
+							}
+							let mut s = String::new();
+							s.push_str("KEY_");
+							s.push_str(&key.to_uppercase());
+							actions.push(Actions::Press(s));
+						}
+						_ => return Err(anyhow!("press is defined as: `press(key)`")),
+					},
+					"release" => match args.as_slice() {
+						[Literal::String(key)] => {
+							if !keycodes::key_exists(key) {
+								return Err(anyhow!("invalid key: {key}"));
+							}
+							let mut s = String::new();
+							s.push_str("KEY_");
+							s.push_str(&key.to_uppercase());
+							actions.push(Actions::Release(s));
+						}
+						_ => return Err(anyhow!("release is defined as: `release(key)`")),
+					},
+					name @ ("click" | "mousedown" | "mouseup") => match args.as_slice() {
+						[Literal::String(button)] if mouse_buttons.contains(&button.to_lowercase().as_str()) => {
+							let button = button.to_lowercase();
+							actions.push(match name {
+								"click" => Actions::Click(button),
+								"mousedown" => Actions::MouseDown(button),
+								_ => Actions::MouseUp(button),
+							});
+						}
+						_ => return Err(anyhow!("{name} is defined as: `{name}(button)`, with one of {}", mouse_buttons.join(", "))),
+					},
+					name @ ("move" | "moverel") => match args.as_slice() {
+						[Literal::Number(x), Literal::Number(y)] => {
+							let (Ok(x), Ok(y)) = (i32::try_from(*x), i32::try_from(*y)) else {
+								return Err(anyhow!("{name} position out of range: {x}, {y}"));
+							};
+							actions.push(if name == "move" {
+								Actions::Move(x, y)
+							} else {
+								Actions::MoveRelative(x, y)
+							});
+						}
+						_ => return Err(anyhow!("{name} is defined as: `{name}(x, y)`")),
+					},
+					"scroll" => match args.as_slice() {
+						[Literal::Number(notches)] if *notches != 0 => {
+							let Ok(notches) = i32::try_from(*notches) else {
+								return Err(anyhow!("scroll distance out of range: {notches}"));
+							};
+							actions.push(Actions::Scroll(notches));
+						}
+						_ => return Err(anyhow!("scroll is defined as: `scroll(notches)`, positive scrolls up")),
+					},
+					_ => unreachable!(),
+				}
+			}
+		}
+	}
+
+	return Ok(actions);
+}
+
+pub fn parse(input: String) -> anyhow::Result<Vec<Actions>> {
+	return parse_with(input, &Parameters::new());
+}
+
+/// Parses a sequence that uses the given parameters as variables
+pub fn parse_with(input: String, parameters: &Parameters) -> anyhow::Result<Vec<Actions>> {
+	if input.chars().all(|c| c.is_whitespace()) {
+		return Ok(Vec::new());
+	}
+
+	let start = std::time::Instant::now();
+
+	let mut rest: &str = input.as_str();
+	let mut variables = parameters.clone();
+	let actions = parse_block(&input, &mut rest, 0, &mut variables)?;
+
+	info!("parsing done; took {}ms", start.elapsed().as_millis());
+	return Ok(actions);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpanKind {
+	String,
+	Key,
+	Action,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+	pub offset: usize,
+	pub len: usize,
+	pub kind: SpanKind,
+}
+
+/// Tokenizes as far as possible without failing, for syntax highlighting.
+pub fn spans(input: &str) -> Vec<Span> {
+	let mut spans: Vec<Span> = Vec::new();
+	let mut offset: usize = 0;
+	let mut rest: &str = input;
+
+	fn inner(rest: &str) -> anyhow::Result<(&str, Token<'_>)> {
+		let res = token(rest).map_err(|e| {
+			error!(?e);
+			return anyhow!("{e}").context("parse error");
+		})?;
+
+		return Ok(res);
+	}
+
+	loop {
+		let res = if let Ok(o) = inner(rest) {
+			o
+		} else {
+			break;
+		};
+
+		let len = rest.len() - res.0.len();
+		rest = res.0;
+
+		let kind = match res.1 {
+			Token::Sequence(_) => Some(SpanKind::String),
+			Token::Key(_) | Token::Chord(_) => Some(SpanKind::Key),
+			Token::Action(_) | Token::Let(_) | Token::Repeat(_) | Token::BlockEnd => Some(SpanKind::Action),
+			_ => None,
+		};
+		if let Some(kind) = kind {
+			spans.push(Span { offset, len, kind });
+		}
+		offset += len;
+
+		if rest.is_empty() || rest.chars().all(|c| c.is_whitespace()) {
+			break;
+		}
+	}
+
+	return spans;
+}
+
+/// Variables used by the sequence before or without a `let`, these need a value when starting it
+pub fn parameters(input: &str) -> Vec<String> {
+	let mut parameters: Vec<String> = Vec::new();
+	let mut defined: Vec<String> = Vec::new();
+	let mut rest: &str = input;
+
+	let mut used = |parts: &[Part], defined: &[String]| {
+		for part in parts {
+			let Part::Variable(name) = part else {
+				continue;
+			};
+
+			if !defined.contains(name) && !parameters.contains(name) {
+				parameters.push(name.clone());
+			}
+		}
+	};
+
+	while let Ok((next, token)) = token(rest) {
+		rest = next;
+		match token {
+			Token::Sequence(parts) => used(&parts, &defined),
+			Token::Let((name, parts)) => {
+				used(&parts, &defined);
+				defined.push(name);
+			}
+			_ => {}
+		}
+
+		if rest.chars().all(|c| c.is_whitespace()) {
+			break;
+		}
+	}
+
+	return parameters;
+}
+
+/// Every parameter of the sequence with an empty value, to check a sequence before its parameters are known
+pub fn placeholders(input: &str) -> Parameters {
+	return parameters(input)
+		.into_iter()
+		.map(|name| (name, String::new()))
+		.collect();
+}
@@ -1,21 +1,29 @@
 use nom::{
 	Parser,
 	branch::alt,
-	bytes::complete::{is_not, take_while_m_n},
+	bytes::complete::{is_not, tag, take_while_m_n},
 	character::complete::{char, multispace1},
 	combinator::{cut, map, map_opt, map_res, value, verify},
 	error::context,
 	multi::fold,
-	sequence::{delimited, preceded},
+	sequence::{delimited, preceded, terminated},
 };
 
-use super::ParseResult;
+use super::{ParseResult, parse_ident};
 
 #[derive(Clone)]
 enum StringFragment<'a> {
 	Literal(&'a str),
 	EscapedChar(char),
 	EscapedWS,
+	Variable(String),
+}
+
+/// Part of a string literal, variables are filled in when the sequence is parsed
+#[derive(Debug, Clone, PartialEq)]
+pub enum Part {
+	Text(String),
+	Variable(String),
 }
 
 fn parse_unicode(input: &str) -> ParseResult<'_, &str, char> {
@@ -36,6 +44,7 @@ fn parse_escaped_char(input: &str) -> ParseResult<'_, &str, char> {
 			value('\t', char('t')),
 			value('\\', char('\\')),
 			value('"', char('"')),
+			value('$', char('$')),
 		)),
 	)
 	.parse(input);
@@ -46,27 +55,46 @@ fn parse_escaped_whitespace(input: &str) -> ParseResult<'_, &str, &str> {
 }
 
 fn parse_literal(input: &str) -> ParseResult<'_, &str, &str> {
-	let not_quote_slash = is_not("\"\\");
+	let not_quote_slash = is_not("\"\\$");
 	return verify(not_quote_slash, |s: &str| !s.is_empty()).parse(input);
 }
 
+fn parse_variable(input: &str) -> ParseResult<'_, &str, String> {
+	return preceded(
+		tag("${"),
+		cut(terminated(context("expected a variable name", parse_ident), context("expected '}'", char('}')))),
+	)
+	.parse(input);
+}
+
 fn parse_fragment(input: &str) -> ParseResult<'_, &str, StringFragment<'_>> {
 	return alt((
 		map(parse_literal, StringFragment::Literal),
+		map(parse_variable, StringFragment::Variable),
+		// a `$` without `{` is typed as is
+		map(tag("$"), StringFragment::Literal),
 		map(parse_escaped_char, StringFragment::EscapedChar),
 		value(StringFragment::EscapedWS, parse_escaped_whitespace),
 	))
 	.parse(input);
 }
 
-pub fn parse_string(input: &str) -> ParseResult<'_, &str, String> {
-	let build = fold(0.., parse_fragment, String::new, |mut s, frag| {
+fn push_text(parts: &mut Vec<Part>, text: &str) {
+	match parts.last_mut() {
+		Some(Part::Text(s)) => s.push_str(text),
+		_ => parts.push(Part::Text(text.to_string())),
+	}
+}
+
+pub fn parse_string(input: &str) -> ParseResult<'_, &str, Vec<Part>> {
+	let build = fold(0.., parse_fragment, Vec::new, |mut parts, frag| {
 		match frag {
-			StringFragment::Literal(lit) => s.push_str(lit),
-			StringFragment::EscapedChar(c) => s.push(c),
+			StringFragment::Literal(lit) => push_text(&mut parts, lit),
+			StringFragment::EscapedChar(c) => push_text(&mut parts, c.encode_utf8(&mut [0; 4])),
 			StringFragment::EscapedWS => {}
+			StringFragment::Variable(name) => parts.push(Part::Variable(name)),
 		}
-		return s;
+		return parts;
 	});
 
 	return delimited(char('"'), cut(build), cut(context("mismatched quotes", char('"')))).parse(input);
@@ -0,0 +1,238 @@
+#[cfg(test)]
+use super::*;
+
+#[test]
+fn test_string_parsing() {
+	let data = "\"tab:\\tafter tab, newline:\\nnew line, quote: \\\", emoji: \\u{1F602}, newline:\\nescaped whitespace: \\    abc\"";
+	let result = strings::parse_string(data);
+	assert_eq!(
+		result,
+		Ok((
+			"",
+			vec![strings::Part::Text(
+				"tab:\tafter tab, newline:\nnew line, quote: \", emoji: 😂, newline:\nescaped whitespace: abc".into()
+			)]
+		))
+	);
+
+	assert!(strings::parse_string("\"this is another test\"").is_ok());
+	assert!(strings::parse_string("\"this is another test").is_err());
+	assert!(strings::parse_string("this is another test\"").is_err());
+	assert!(strings::parse_string("\"this \\h is another test\"").is_err());
+}
+
+#[test]
+fn test_keyword_parsing() {
+	assert_eq!(key("Space Tab").unwrap(), (" Tab", "Space".into()));
+}
+
+#[test]
+fn test_func_parsing() {
+	assert!(func("delay(1, 2)").is_ok());
+	assert!(parse("delay(1, 2)".into()).is_err());
+	assert!(func("delay(1)").is_ok());
+	assert!(func("press(Space)").is_ok());
+	assert!(parse("not_a_func(Space)".into()).is_err());
+}
+
+#[test]
+fn test_generic_parsing() {
+	assert!(parse("Space Tab \"this is another test\"".into()).is_ok());
+	assert!(parse("Space Tab \"this is another test".into()).is_err());
+	assert!(parse("Space Tab this is another test\"".into()).is_err());
+
+	assert!(parse("Space Tab move delay(2)".into()).is_ok());
+	assert!(parse("Space Tab move dely".into()).is_err());
+}
+
+#[test]
+fn test_number_parsing() {
+	assert!(parse_number("234").is_ok());
+	assert!(parse_number("-234").is_ok());
+	assert!(parse_number("a234").is_err());
+	assert_eq!(parse_number("-23a4"), Ok(("a4", -23)));
+}
+
+#[test]
+fn test_actions_parsing() {
+	let vec = parse("Space Tab \"a\\n \\t \\\\ \\\"test\" delay(2)".into()).unwrap();
+	assert_eq!(
+		vec,
+		[
+			Actions::PressAndRelease("KEY_SPACE".into()),
+			Actions::PressAndRelease("KEY_TAB".into()),
+			Actions::PressAndRelease("KEY_A".into()),
+			Actions::PressAndRelease("KEY_ENTER".into()),
+			Actions::PressAndRelease("KEY_SPACE".into()),
+			Actions::PressAndRelease("KEY_TAB".into()),
+			Actions::PressAndRelease("KEY_SPACE".into()),
+			Actions::PressAndRelease("KEY_BACKSLASH".into()),
+			Actions::PressAndRelease("KEY_SPACE".into()),
+			Actions::Press("KEY_LEFTSHIFT".into()),
+			Actions::Press("KEY_APOSTROPHE".into()),
+			Actions::Release("KEY_LEFTSHIFT".into()),
+			Actions::Release("KEY_APOSTROPHE".into()),
+			Actions::PressAndRelease("KEY_T".into()),
+			Actions::PressAndRelease("KEY_E".into()),
+			Actions::PressAndRelease("KEY_S".into()),
+			Actions::PressAndRelease("KEY_T".into()),
+			Actions::Delay(2),
+		]
+	);
+}
+
+#[test]
+fn test_repeat_parsing() {
+	assert_eq!(
+		parse("repeat(3) { Tab } \"a\"".into()).unwrap(),
+		vec![
+			Actions::PressAndRelease("KEY_TAB".into()),
+			Actions::PressAndRelease("KEY_TAB".into()),
+			Actions::PressAndRelease("KEY_TAB".into()),
+			Actions::PressAndRelease("KEY_A".into()),
+		]
+	);
+
+	assert_eq!(
+		parse("repeat(2){repeat (2) {Tab} Enter}".into()).unwrap(),
+		vec![
+			Actions::PressAndRelease("KEY_TAB".into()),
+			Actions::PressAndRelease("KEY_TAB".into()),
+			Actions::PressAndRelease("KEY_ENTER".into()),
+			Actions::PressAndRelease("KEY_TAB".into()),
+			Actions::PressAndRelease("KEY_TAB".into()),
+			Actions::PressAndRelease("KEY_ENTER".into()),
+		]
+	);
+
+	assert_eq!(parse("repeat(0) { Tab }".into()).unwrap(), vec![]);
+	assert!(parse("repeat(3) { Tab".into()).is_err());
+	assert!(parse("Tab }".into()).is_err());
+	assert!(parse("repeat(x) { Tab }".into()).is_err());
+	assert!(parse("repeat(3) Tab".into()).is_err());
+	assert!(parse("repeat(-1) { Tab }".into()).is_err());
+	assert!(parse("repeat(1000) { repeat(1000) { Tab } }".into()).is_err());
+	// empty blocks must not spin through the count
+	assert_eq!(parse("repeat(9223372036854775807) { }".into()).unwrap(), vec![]);
+	assert_eq!(parse("repeat(9223372036854775807) { let x = \"a\" }".into()).unwrap(), vec![]);
+}
+
+#[test]
+fn test_repeat_spans() {
+	let kinds: Vec<SpanKind> = spans("repeat(2) { Tab }")
+		.into_iter()
+		.map(|s| s.kind)
+		.collect();
+	assert_eq!(kinds, vec![SpanKind::Action, SpanKind::Key, SpanKind::Action]);
+}
+
+#[test]
+fn test_variables() {
+	assert_eq!(
+		strings::parse_string("\"a${name}b $5 \\${x}\""),
+		Ok((
+			"",
+			vec![
+				strings::Part::Text("a".into()),
+				strings::Part::Variable("name".into()),
+				strings::Part::Text("b $5 ${x}".into()),
+			]
+		))
+	);
+	assert!(strings::parse_string("\"${name\"").is_err());
+
+	assert_eq!(
+		parse("let a = \"1\" let b = \"${a}2\" \"${b}\"".into()).unwrap(),
+		vec![Actions::PressAndRelease("KEY_1".into()), Actions::PressAndRelease("KEY_2".into())]
+	);
+	assert!(parse("\"${a}\"".into()).is_err());
+	assert!(parse("let a \"1\"".into()).is_err());
+	assert!(parse("let = \"1\"".into()).is_err());
+
+	let parameters = Parameters::from([("a".to_string(), "3".to_string())]);
+	assert_eq!(parse_with("\"${a}\"".into(), &parameters).unwrap(), vec![Actions::PressAndRelease("KEY_3".into())]);
+}
+
+#[test]
+fn test_parameters() {
+	assert_eq!(
+		parameters("let a = \"${b}\" \"${a}${c}\" repeat(2) { \"${b}\" } let c = \"\""),
+		vec!["b".to_string(), "c".to_string()]
+	);
+	assert_eq!(parameters("Tab \"a\""), Vec::<String>::new());
+
+	let input = "\"${name}\" Tab";
+	assert!(parse(input.into()).is_err());
+	assert!(parse_with(input.into(), &placeholders(input)).is_ok());
+}
+
+#[test]
+fn test_chord_parsing() {
+	let expected = vec![
+		Actions::Press("KEY_LEFTCTRL".into()),
+		Actions::Press("KEY_LEFTSHIFT".into()),
+		Actions::Press("KEY_T".into()),
+		Actions::Release("KEY_T".into()),
+		Actions::Release("KEY_LEFTSHIFT".into()),
+		Actions::Release("KEY_LEFTCTRL".into()),
+	];
+	assert_eq!(parse("ctrl+shift+t".into()).unwrap(), expected);
+	assert_eq!(parse("Control+Shift+T".into()).unwrap(), expected);
+	assert_eq!(parse("<C-S-t>".into()).unwrap(), expected);
+
+	assert_eq!(
+		parse("a+b <RightAlt-F4>".into()).unwrap(),
+		vec![
+			Actions::Press("KEY_A".into()),
+			Actions::Press("KEY_B".into()),
+			Actions::Release("KEY_B".into()),
+			Actions::Release("KEY_A".into()),
+			Actions::Press("KEY_RIGHTALT".into()),
+			Actions::Press("KEY_F4".into()),
+			Actions::Release("KEY_F4".into()),
+			Actions::Release("KEY_RIGHTALT".into()),
+		]
+	);
+
+	assert!(parse("ctrl+".into()).is_err());
+	assert!(parse("ctrl+nope".into()).is_err());
+	assert!(parse("hyper+t".into()).is_err());
+	assert!(parse("<C-t".into()).is_err());
+	assert!(parse("<>".into()).is_err());
+
+	let kinds: Vec<SpanKind> = spans("ctrl+t \"a\"").into_iter().map(|s| s.kind).collect();
+	assert_eq!(kinds, vec![SpanKind::Key, SpanKind::String]);
+}
+
+#[test]
+fn test_mouse_actions() {
+	assert_eq!(
+		parse("move(100, 200) click(left) mousedown(Right) moverel(-5, 0) mouseup(right) scroll(-3) Tab".into()).unwrap(),
+		vec![
+			Actions::Move(100, 200),
+			Actions::Click("left".into()),
+			Actions::MouseDown("right".into()),
+			Actions::MoveRelative(-5, 0),
+			Actions::MouseUp("right".into()),
+			Actions::Scroll(-3),
+			Actions::PressAndRelease("KEY_TAB".into()),
+		]
+	);
+
+	// without parentheses `move` is still the key
+	assert_eq!(parse("move".into()).unwrap(), vec![Actions::PressAndRelease("KEY_MOVE".into())]);
+
+	assert!(parse("click(nope)".into()).is_err());
+	assert!(parse("click()".into()).is_err());
+	assert!(parse("move(1)".into()).is_err());
+	assert!(parse("move(1, 9999999999)".into()).is_err());
+	assert!(parse("scroll(0)".into()).is_err());
+	assert!(parse("click".into()).is_err());
+}
+
+#[test]
+fn test_untypeable_characters() {
+	assert!(parse("\"naïve\"".into()).is_err());
+	assert!(parse("\"\\u{1F602}\"".into()).is_err());
+	assert_eq!(parse("\"Hi\"".into()).unwrap().len(), 5);
+}
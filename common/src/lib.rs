@@ -1,8 +1,13 @@
 use serde::{Deserialize, Serialize};
 
 pub mod actions;
+pub mod key_parser;
+pub mod keycodes;
+mod keysyms;
+pub mod macro_file;
 pub mod prelude;
 pub mod settings;
+pub mod xkb;
 
 pub trait Json<T: for<'de> Deserialize<'de> + Serialize = Self> {
 	fn decode<S: Into<String>>(json: S) -> Result<T, serde_json::Error> {
@@ -14,25 +19,175 @@ pub trait Json<T: for<'de> Deserialize<'de> + Serialize = Self> {
 	}
 }
 
+/// Stops the job with the given id, or every job if `None`
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct StopClicking {
+	#[serde(default)]
+	pub job: Option<u64>,
+}
+
+/// Random offset added to a value, in the unit of that value
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "distribution")]
+pub enum Randomization {
+	/// Anywhere between `-range` and `range`
+	Uniform {
+		range: f64,
+	},
+	Gaussian {
+		mean: f64,
+		stddev: f64,
+	},
+}
+
+/// How the cursor travels to a click position instead of jumping there
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "path")]
+pub enum Movement {
+	/// Straight line, speeding up and slowing down again over `duration` ms
+	Eased { duration: u64 },
+	/// Randomly curved line, eased the same way
+	Bezier { duration: u64 },
+}
+
+impl Movement {
+	pub fn duration(&self) -> u64 {
+		return match *self {
+			Movement::Eased { duration } | Movement::Bezier { duration } => duration,
+		};
+	}
+}
+
+fn default_clicks() -> u32 {
+	return 1;
+}
+
+fn default_click_spacing() -> u64 {
+	return 50;
+}
+
+/// One stop of a click route, waiting `delay` ms after clicking before moving on to the next point
 #[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct StopClicking {}
+pub struct RoutePoint {
+	pub position: (i32, i32),
+	pub button: String,
+	#[serde(default = "default_clicks")]
+	pub clicks: u32,
+	pub delay: u64,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct RepeatingMouseClick {
 	pub button: String,
-	pub typ: String,
+	/// Clicks done in quick succession every interval, 2 for a double click, 3 for a triple click
+	#[serde(default = "default_clicks")]
+	pub clicks: u32,
 	pub amount: u64,
 	pub position: (Option<i32>, Option<i32>),
 	pub interval: u64,
+	/// How long the button is held down for every click in ms
+	#[serde(default)]
+	pub hold_duration: u64,
+	/// Time between releasing one click and pressing the next one of the same multi-click in ms
+	#[serde(default = "default_click_spacing")]
+	pub click_spacing: u64,
+	#[serde(default)]
+	pub interval_random: Option<Randomization>,
+	/// Offset in pixels, applied to each set axis of `position` separately
+	#[serde(default)]
+	pub position_random: Option<Randomization>,
+	/// Points clicked in order instead of `position`, using their own button, clicks and delay.
+	/// `amount` then counts how often the whole route is run
+	#[serde(default)]
+	pub route: Vec<RoutePoint>,
+	/// Moves to every position along a path instead of jumping there
+	#[serde(default)]
+	pub movement: Option<Movement>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct RepeatingKeyboardClick {
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
 	pub buttons: Vec<actions::Actions>,
 	pub amount: u64,
 	pub interval: u64,
 	pub delay_before_repeat: u64,
 	pub hold_duration: u64,
+	#[serde(default)]
+	pub interval_random: Option<Randomization>,
+}
+
+/// Holds a mouse button down, at `position` if set. A `duration` of 0 holds until stopped
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MouseHold {
+	pub button: String,
+	pub duration: u64,
+	#[serde(default)]
+	pub position: (Option<i32>, Option<i32>),
+}
+
+/// Presses a mouse button at `from`, moves to `to` over `duration` ms and releases it there
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MouseDrag {
+	pub button: String,
+	pub from: (i32, i32),
+	pub to: (i32, i32),
+	pub duration: u64,
+}
+
+/// Turns the scroll wheel by `distance` every `interval` ms, `amount` times or until stopped if 0.
+/// `axis` is `vertical` or `horizontal`, a positive `distance` scrolls up or right.
+/// With `hi_res` the distance is in 1/120 of a notch, like a high-resolution wheel reports it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MouseScroll {
+	pub axis: String,
+	pub distance: i32,
+	#[serde(default)]
+	pub hi_res: bool,
+	pub amount: u64,
+	pub interval: u64,
+	#[serde(default)]
+	pub interval_random: Option<Randomization>,
+}
+
+/// One input event of a recorded macro
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MacroEvent {
+	/// Milliseconds since the first event of the macro
+	pub time: u64,
+	/// evdev name of the key, button or axis, e.g. `KEY_A`, `BTN_LEFT` or `REL_X`
+	pub code: String,
+	/// 1 for a press and 0 for a release, or the distance of a relative motion
+	pub value: i32,
+}
+
+fn default_speed() -> f64 {
+	return 1.0;
+}
+
+/// Plays back recorded events through the virtual keyboard and mouse, `amount` times or until stopped if 0
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReplayMacro {
+	pub events: Vec<MacroEvent>,
+	/// Playback speed, 2.0 plays twice as fast as recorded, from 0.01 to 100
+	#[serde(default = "default_speed")]
+	pub speed: f64,
+	pub amount: u64,
+	#[serde(default)]
+	pub delay_before_repeat: u64,
+}
+
+/// Starts recording the physical keyboards and mice
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StartRecording {}
+
+/// Stops recording, answered with a `Recording`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StopRecording {}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Recording {
+	pub events: Vec<MacroEvent>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -40,6 +195,161 @@ pub struct MouseClick {
 	pub button: String,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type")]
+pub enum Job {
+	RepeatingMouseClick(RepeatingMouseClick),
+	RepeatingKeyboardClick(RepeatingKeyboardClick),
+	MouseHold(MouseHold),
+	MouseDrag(MouseDrag),
+	MouseScroll(MouseScroll),
+	ReplayMacro(ReplayMacro),
+}
+
+impl Job {
+	pub fn kind(&self) -> &'static str {
+		return match self {
+			Job::RepeatingMouseClick(_) => "RepeatingMouseClick",
+			Job::RepeatingKeyboardClick(_) => "RepeatingKeyboardClick",
+			Job::MouseHold(_) => "MouseHold",
+			Job::MouseDrag(_) => "MouseDrag",
+			Job::MouseScroll(_) => "MouseScroll",
+			Job::ReplayMacro(_) => "ReplayMacro",
+		};
+	}
+
+	pub fn amount(&self) -> u64 {
+		return match self {
+			Job::RepeatingMouseClick(job) => job.amount,
+			Job::RepeatingKeyboardClick(job) => job.amount,
+			Job::MouseScroll(job) => job.amount,
+			Job::ReplayMacro(job) => job.amount,
+			Job::MouseHold(_) | Job::MouseDrag(_) => 1,
+		};
+	}
+}
+
+impl From<Job> for Message {
+	fn from(job: Job) -> Self {
+		return match job {
+			Job::RepeatingMouseClick(job) => Message::RepeatingMouseClick(job),
+			Job::RepeatingKeyboardClick(job) => Message::RepeatingKeyboardClick(job),
+			Job::MouseHold(job) => Message::MouseHold(job),
+			Job::MouseDrag(job) => Message::MouseDrag(job),
+			Job::MouseScroll(job) => Message::MouseScroll(job),
+			Job::ReplayMacro(job) => Message::ReplayMacro(job),
+		};
+	}
+}
+
+impl TryFrom<Message> for Job {
+	type Error = Message;
+
+	fn try_from(msg: Message) -> Result<Self, Self::Error> {
+		return Ok(match msg {
+			Message::RepeatingMouseClick(job) => Job::RepeatingMouseClick(job),
+			Message::RepeatingKeyboardClick(job) => Job::RepeatingKeyboardClick(job),
+			Message::MouseHold(job) => Job::MouseHold(job),
+			Message::MouseDrag(job) => Job::MouseDrag(job),
+			Message::MouseScroll(job) => Job::MouseScroll(job),
+			Message::ReplayMacro(job) => Job::ReplayMacro(job),
+			msg => return Err(msg),
+		});
+	}
+}
+
+/// Appends a job to the daemon's queue. Jobs run one after another, unless `parallel` is set,
+/// in which case the job runs at the same time as the job queued before it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EnqueueJob {
+	#[serde(default)]
+	pub name: Option<String>,
+	pub job: Job,
+	#[serde(default)]
+	pub parallel: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JobQueued {
+	pub id: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ListJobs {}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JobInfo {
+	pub id: u64,
+	pub name: Option<String>,
+	pub job: String,
+	pub parallel: bool,
+	pub running: bool,
+	pub clicked: u64,
+	pub amount: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct JobList {
+	pub jobs: Vec<JobInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GetStatus {}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Status {
+	/// Kind of the last job, e.g. `RepeatingMouseClick`, or `None` if nothing ran yet
+	pub job: Option<String>,
+	#[serde(default)]
+	pub id: Option<u64>,
+	/// `false` once the job was stopped or a finite run has finished
+	pub running: bool,
+	pub clicked: u64,
+	pub amount: u64,
+	pub index: usize,
+	/// Milliseconds since the job started
+	pub elapsed: u64,
+	/// Amount of jobs in the queue, including running ones
+	#[serde(default)]
+	pub queued: usize,
+}
+
+/// Keeps a unix socket connection open and streams job events as newline-delimited json
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Subscribe {}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JobStarted {
+	#[serde(default)]
+	pub id: u64,
+	pub job: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JobProgress {
+	#[serde(default)]
+	pub id: u64,
+	pub job: String,
+	pub clicked: u64,
+	pub amount: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JobFinished {
+	#[serde(default)]
+	pub id: u64,
+	pub job: String,
+	pub clicked: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JobFailed {
+	#[serde(default)]
+	pub id: u64,
+	pub job: String,
+	pub msg: String,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ConfirmResponse {}
 
@@ -54,7 +364,25 @@ pub enum Message {
 	MouseClick(MouseClick),
 	RepeatingMouseClick(RepeatingMouseClick),
 	RepeatingKeyboardClick(RepeatingKeyboardClick),
+	MouseHold(MouseHold),
+	MouseDrag(MouseDrag),
+	MouseScroll(MouseScroll),
+	ReplayMacro(ReplayMacro),
 	StopClicking(StopClicking),
+	EnqueueJob(EnqueueJob),
+	JobQueued(JobQueued),
+	ListJobs(ListJobs),
+	JobList(JobList),
+	GetStatus(GetStatus),
+	Status(Status),
+	Subscribe(Subscribe),
+	StartRecording(StartRecording),
+	StopRecording(StopRecording),
+	Recording(Recording),
+	JobStarted(JobStarted),
+	JobProgress(JobProgress),
+	JobFinished(JobFinished),
+	JobFailed(JobFailed),
 	ConfirmResponse(ConfirmResponse),
 	Error(Error),
 }
@@ -0,0 +1,169 @@
+use anyhow::{Context, anyhow};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::key_parser::{self, Parameters};
+use crate::{EnqueueJob, Job};
+
+mod tests;
+
+/// Version written to new macro files, files with a newer version are refused
+pub const VERSION: u32 = 1;
+
+/// A job of a macro file, the fields of the job itself are stored next to `name` and `parallel`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MacroJob {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+	/// Runs at the same time as the job before it
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	pub parallel: bool,
+	/// Key sequence in the same syntax as the key sequence editor, used instead of `buttons` for keyboard jobs
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub sequence: Option<String>,
+	#[serde(flatten)]
+	pub job: Job,
+}
+
+impl MacroJob {
+	pub fn new(job: Job) -> Self {
+		return Self {
+			name: None,
+			parallel: false,
+			sequence: None,
+			job,
+		};
+	}
+
+	/// The job with its key sequence parsed, as it is sent to the daemon
+	pub fn resolve(&self, parameters: &Parameters) -> anyhow::Result<EnqueueJob> {
+		let job = self.expand(parameters)?;
+		match job.job {
+			Job::RepeatingKeyboardClick(ref keyboard) if keyboard.buttons.is_empty() => {
+				return Err(anyhow!("keyboard job has no actions, it needs a sequence or buttons that press something"));
+			}
+			_ => {}
+		}
+
+		return Ok(job);
+	}
+
+	fn expand(&self, parameters: &Parameters) -> anyhow::Result<EnqueueJob> {
+		let mut job = self.job.clone();
+		match (&mut job, &self.sequence) {
+			(_, None) => {}
+			(Job::RepeatingKeyboardClick(job), Some(_)) if !job.buttons.is_empty() => return Err(anyhow!("keyboard job has both a sequence and buttons")),
+			(Job::RepeatingKeyboardClick(job), Some(sequence)) => job.buttons = key_parser::parse_with(sequence.clone(), parameters).context("invalid key sequence")?,
+			(job, Some(_)) => return Err(anyhow!("only keyboard jobs can have a sequence, not {}", job.kind())),
+		}
+
+		return Ok(EnqueueJob {
+			name: self.name.clone(),
+			job,
+			parallel: self.parallel,
+		});
+	}
+}
+
+/// Shareable set of jobs, stored as pretty-printed json so it can be edited by hand and kept in git
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MacroFile {
+	pub version: u32,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub description: Option<String>,
+	/// Run one after another like queued jobs
+	pub jobs: Vec<MacroJob>,
+}
+
+impl MacroFile {
+	pub fn new(jobs: Vec<MacroJob>) -> Self {
+		return Self {
+			version: VERSION,
+			name: None,
+			description: None,
+			jobs,
+		};
+	}
+
+	pub fn parse(json: &str) -> anyhow::Result<Self> {
+		#[derive(Deserialize)]
+		struct Header {
+			version: u32,
+		}
+
+		// checked first so a newer file is not reported as a parse error of a field that changed
+		let header: Header = serde_json::from_str(json).context("not a macro file")?;
+		match header.version {
+			0 => return Err(anyhow!("invalid macro file version 0")),
+			v if v > VERSION => {
+				return Err(anyhow!(
+					"macro file version {v} is newer than the supported version {VERSION}, update autoclicker to use it"
+				));
+			}
+			_ => {}
+		}
+
+		let file: Self = serde_json::from_str(json).context("invalid macro file")?;
+		// parameters are only known when running the file
+		let placeholders: Parameters = file
+			.parameters()
+			.into_iter()
+			.map(|name| (name, String::new()))
+			.collect();
+		for (i, job) in file.jobs.iter().enumerate() {
+			// a sequence made of parameters only is empty until they are filled in
+			let checked = match job.sequence {
+				Some(ref sequence) if !key_parser::parameters(sequence).is_empty() => job.expand(&placeholders),
+				_ => job.resolve(&placeholders),
+			};
+			checked.with_context(|| format!("job {}", i + 1))?;
+		}
+
+		return Ok(file);
+	}
+
+	pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+		let path = path.as_ref();
+		let json = std::fs::read_to_string(path).with_context(|| format!("could not read {}", path.display()))?;
+		return Self::parse(&json).with_context(|| format!("could not load {}", path.display()));
+	}
+
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+		let path = path.as_ref();
+		std::fs::write(path, self.to_json()? + "\n").with_context(|| format!("could not write {}", path.display()))?;
+		return Ok(());
+	}
+
+	pub fn to_json(&self) -> anyhow::Result<String> {
+		return Ok(serde_json::to_string_pretty(self)?);
+	}
+
+	/// Variables the key sequences of the jobs use without defining them
+	pub fn parameters(&self) -> Vec<String> {
+		let mut parameters: Vec<String> = Vec::new();
+		for sequence in self.jobs.iter().filter_map(|job| job.sequence.as_ref()) {
+			for name in key_parser::parameters(sequence) {
+				if !parameters.contains(&name) {
+					parameters.push(name);
+				}
+			}
+		}
+
+		return parameters;
+	}
+
+	/// Every job ready to be queued on the daemon, in order
+	pub fn resolve(&self, parameters: &Parameters) -> anyhow::Result<Vec<EnqueueJob>> {
+		return self
+			.jobs
+			.iter()
+			.enumerate()
+			.map(|(i, job)| {
+				job.resolve(parameters)
+					.with_context(|| format!("job {}", i + 1))
+			})
+			.collect();
+	}
+}
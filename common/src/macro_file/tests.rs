@@ -0,0 +1,116 @@
+#[cfg(test)]
+use super::*;
+
+#[cfg(test)]
+use crate::{RepeatingKeyboardClick, actions::Actions};
+
+#[test]
+fn test_macro_file_round_trip() {
+	let json = r#"{
+	"version": 1,
+	"name": "farm",
+	"jobs": [
+		{ "type": "RepeatingMouseClick", "button": "left", "amount": 10, "position": [100, null], "interval": 50 },
+		{ "type": "RepeatingKeyboardClick", "parallel": true, "sequence": "Space \"hi\"", "amount": 1, "interval": 25, "delay_before_repeat": 0, "hold_duration": 0 },
+		{ "type": "MouseScroll", "name": "scroll down", "axis": "vertical", "distance": -3, "amount": 5, "interval": 100 }
+	]
+}"#;
+
+	let file = MacroFile::parse(json).unwrap();
+	assert_eq!(file.name.as_deref(), Some("farm"));
+	assert_eq!(file.jobs.len(), 3);
+
+	let jobs = file.resolve(&Parameters::new()).unwrap();
+	assert!(jobs[1].parallel);
+	assert_eq!(jobs[2].name.as_deref(), Some("scroll down"));
+	let Job::RepeatingKeyboardClick(keyboard) = &jobs[1].job else {
+		panic!("expected a keyboard job");
+	};
+	assert_eq!(keyboard.buttons[0], Actions::PressAndRelease("KEY_SPACE".into()));
+	assert_eq!(keyboard.buttons.len(), 3);
+
+	// the sequence is kept as written instead of being expanded into buttons
+	let saved = file.to_json().unwrap();
+	assert!(!saved.contains("buttons"));
+	assert_eq!(
+		MacroFile::parse(&saved)
+			.unwrap()
+			.resolve(&Parameters::new())
+			.unwrap()
+			.len(),
+		3
+	);
+}
+
+#[test]
+fn test_macro_file_version() {
+	assert!(MacroFile::parse(r#"{ "version": 1, "jobs": [] }"#).is_ok());
+	assert!(MacroFile::parse(r#"{ "version": 0, "jobs": [] }"#).is_err());
+	assert!(MacroFile::parse(r#"{ "jobs": [] }"#).is_err());
+
+	let err = MacroFile::parse(r#"{ "version": 2, "jobs": [{ "type": "SomethingNew" }] }"#).unwrap_err();
+	assert!(err.to_string().contains("newer"));
+}
+
+#[test]
+fn test_macro_file_sequence() {
+	assert!(
+		MacroFile::parse(
+			r#"{ "version": 1, "jobs": [{ "type": "RepeatingKeyboardClick", "sequence": "not_a_key", "amount": 1, "interval": 25, "delay_before_repeat": 0, "hold_duration": 0 }] }"#
+		)
+		.is_err()
+	);
+	assert!(MacroFile::parse(r#"{ "version": 1, "jobs": [{ "type": "MouseHold", "sequence": "Space", "button": "left", "duration": 0 }] }"#).is_err());
+
+	let job = MacroJob {
+		sequence: Some("Space".into()),
+		..MacroJob::new(Job::RepeatingKeyboardClick(RepeatingKeyboardClick {
+			buttons: vec![Actions::PressAndRelease("KEY_TAB".into())],
+			amount: 1,
+			interval: 25,
+			delay_before_repeat: 0,
+			hold_duration: 0,
+			interval_random: None,
+		}))
+	};
+	assert!(job.resolve(&Parameters::new()).is_err());
+}
+
+#[test]
+fn test_macro_file_parameters() {
+	let file = MacroFile::parse(
+		r#"{ "version": 1, "jobs": [{ "type": "RepeatingKeyboardClick", "sequence": "\"${user}\" Tab \"${password}\"", "amount": 1, "interval": 25, "delay_before_repeat": 0, "hold_duration": 0 }] }"#,
+	)
+	.unwrap();
+	assert_eq!(file.parameters(), vec!["user".to_string(), "password".to_string()]);
+	assert!(file.resolve(&Parameters::new()).is_err());
+
+	let parameters = Parameters::from([("user".to_string(), "a".to_string()), ("password".to_string(), "b".to_string())]);
+	let jobs = file.resolve(&parameters).unwrap();
+	let Job::RepeatingKeyboardClick(keyboard) = &jobs[0].job else {
+		panic!("expected a keyboard job");
+	};
+	assert_eq!(keyboard.buttons.len(), 3);
+}
+
+#[test]
+fn test_macro_file_empty_keyboard_job() {
+	let keyboard = |sequence: &str| {
+		format!(r#"{{ "version": 1, "jobs": [{{ "type": "RepeatingKeyboardClick", {sequence}"amount": 1, "interval": 25, "delay_before_repeat": 0, "hold_duration": 0 }}] }}"#)
+	};
+
+	assert!(MacroFile::parse(&keyboard("")).is_err());
+	assert!(MacroFile::parse(&keyboard(r#""sequence": "repeat(0) { Tab }", "#)).is_err());
+	assert!(MacroFile::parse(&keyboard(r#""sequence": "let x = \"a\"", "#)).is_err());
+
+	// only empty once the parameter is known
+	let file = MacroFile::parse(&keyboard(r#""sequence": "\"${text}\"", "#)).unwrap();
+	assert!(
+		file.resolve(&Parameters::from([("text".to_string(), "a".to_string())]))
+			.is_ok()
+	);
+	assert!(
+		file.resolve(&Parameters::from([("text".to_string(), String::new())]))
+			.is_err()
+	);
+}
@@ -1 +1,8 @@
-pub use crate::{ConfirmResponse, Error as ErrorResponse, Json, Message, MouseClick, RepeatingKeyboardClick, RepeatingMouseClick, StopClicking, actions::Actions, settings};
+pub use crate::{
+	ConfirmResponse, EnqueueJob, Error as ErrorResponse, GetStatus, Job, JobFailed, JobFinished, JobInfo, JobList, JobProgress, JobQueued, JobStarted, Json, ListJobs, MacroEvent,
+	Message, MouseClick, MouseDrag, MouseHold, MouseScroll, Movement, Randomization, Recording, RepeatingKeyboardClick, RepeatingMouseClick, ReplayMacro, RoutePoint,
+	StartRecording, Status, StopClicking, StopRecording, Subscribe,
+	actions::Actions,
+	macro_file::{MacroFile, MacroJob},
+	settings,
+};
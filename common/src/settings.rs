@@ -3,8 +3,8 @@ use anyhow::Context;
 mod versions;
 pub use versions::*;
 
-pub type Settings = v5::Settings;
-pub use v5 as latest;
+pub type Settings = v8::Settings;
+pub use v8 as latest;
 
 macro_rules! generate_trait {
 	($($version:tt),* $(,)?) => {
@@ -57,7 +57,7 @@ macro_rules! generate_whole {
 	};
 }
 
-generate_whole!(v4, v3, v2, v1);
+generate_whole!(v7, v6, v5, v4, v3, v2, v1);
 
 impl Latest for Settings {
 	fn v1(old: v1::Settings) -> Self {
@@ -75,6 +75,7 @@ impl Latest for Settings {
 			general: latest::GeneralSettings {
 				socket_path: Some(old.general.socket_path),
 				communication_method: latest::Methods::UnixSocket,
+				..Default::default()
 			},
 			client: latest::ClientSettings {
 				disable_window_controls: old.client.disable_window_controls,
@@ -86,11 +87,13 @@ impl Latest for Settings {
 				mouse: latest::MouseSettings {
 					added_delay: old.daemon.mouse.added_delay,
 					disabled: old.daemon.mouse.disabled,
+					..Default::default()
 				},
 				keyboard: latest::KeyboardSettings {
 					added_delay: old.daemon.keyboard.added_delay,
 					disabled: old.daemon.keyboard.disabled,
 				},
+				..Default::default()
 			},
 		};
 	}
@@ -104,6 +107,7 @@ impl Latest for Settings {
 				} else {
 					latest::Methods::UnixSocket
 				},
+				..Default::default()
 			},
 			client: latest::ClientSettings {
 				disable_window_controls: old.client.disable_window_controls,
@@ -115,11 +119,13 @@ impl Latest for Settings {
 				mouse: latest::MouseSettings {
 					added_delay: old.daemon.mouse.added_delay,
 					disabled: old.daemon.mouse.disabled,
+					..Default::default()
 				},
 				keyboard: latest::KeyboardSettings {
 					added_delay: old.daemon.keyboard.added_delay,
 					disabled: old.daemon.keyboard.disabled,
 				},
+				..Default::default()
 			},
 		};
 	}
@@ -133,6 +139,7 @@ impl Latest for Settings {
 				} else {
 					latest::Methods::UnixSocket
 				},
+				..Default::default()
 			},
 			client: latest::ClientSettings {
 				disable_window_controls: old.client.disable_window_controls,
@@ -145,11 +152,127 @@ impl Latest for Settings {
 				mouse: latest::MouseSettings {
 					added_delay: old.daemon.mouse.added_delay,
 					disabled: old.daemon.mouse.disabled,
+					..Default::default()
 				},
 				keyboard: latest::KeyboardSettings {
 					added_delay: old.daemon.keyboard.added_delay,
 					disabled: old.daemon.keyboard.disabled,
 				},
+				..Default::default()
+			},
+		};
+	}
+
+	fn v5(old: v5::Settings) -> Self {
+		return Self {
+			general: latest::GeneralSettings {
+				socket_path: old.general.socket_path,
+				communication_method: if old.general.communication_method == v5::Methods::DBus {
+					latest::Methods::DBus
+				} else {
+					latest::Methods::UnixSocket
+				},
+				..Default::default()
+			},
+			client: latest::ClientSettings {
+				disable_window_controls: old.client.disable_window_controls,
+				notification: old.client.notification,
+				disable_global_shortcut: old.client.disable_global_shortcut,
+			},
+			daemon: latest::DaemonSettings {
+				hyprland_ipc: old.daemon.hyprland_ipc,
+				dry_run: old.daemon.dry_run,
+				mouse: latest::MouseSettings {
+					added_delay: old.daemon.mouse.added_delay,
+					disabled: old.daemon.mouse.disabled,
+					..Default::default()
+				},
+				keyboard: latest::KeyboardSettings {
+					added_delay: old.daemon.keyboard.added_delay,
+					disabled: old.daemon.keyboard.disabled,
+				},
+				..Default::default()
+			},
+		};
+	}
+
+	fn v6(old: v6::Settings) -> Self {
+		return Self {
+			general: latest::GeneralSettings {
+				socket_path: old.general.socket_path,
+				communication_method: if old.general.communication_method == v6::Methods::DBus {
+					latest::Methods::DBus
+				} else {
+					latest::Methods::UnixSocket
+				},
+				..Default::default()
+			},
+			client: latest::ClientSettings {
+				disable_window_controls: old.client.disable_window_controls,
+				notification: old.client.notification,
+				disable_global_shortcut: old.client.disable_global_shortcut,
+			},
+			daemon: latest::DaemonSettings {
+				hyprland_ipc: old.daemon.hyprland_ipc,
+				dry_run: old.daemon.dry_run,
+				mouse: latest::MouseSettings {
+					added_delay: old.daemon.mouse.added_delay,
+					disabled: old.daemon.mouse.disabled,
+					pointer: if old.daemon.mouse.pointer == v6::PointerDevice::Absolute {
+						latest::PointerDevice::Absolute
+					} else {
+						latest::PointerDevice::Relative
+					},
+					screen_size: old.daemon.mouse.screen_size,
+				},
+				keyboard: latest::KeyboardSettings {
+					added_delay: old.daemon.keyboard.added_delay,
+					disabled: old.daemon.keyboard.disabled,
+				},
+				..Default::default()
+			},
+		};
+	}
+
+	fn v7(old: v7::Settings) -> Self {
+		return Self {
+			general: latest::GeneralSettings {
+				socket_path: old.general.socket_path,
+				communication_method: if old.general.communication_method == v7::Methods::DBus {
+					latest::Methods::DBus
+				} else {
+					latest::Methods::UnixSocket
+				},
+				..Default::default()
+			},
+			client: latest::ClientSettings {
+				disable_window_controls: old.client.disable_window_controls,
+				notification: old.client.notification,
+				disable_global_shortcut: old.client.disable_global_shortcut,
+			},
+			daemon: latest::DaemonSettings {
+				hyprland_ipc: old.daemon.hyprland_ipc,
+				dry_run: old.daemon.dry_run,
+				mouse: latest::MouseSettings {
+					added_delay: old.daemon.mouse.added_delay,
+					disabled: old.daemon.mouse.disabled,
+					pointer: if old.daemon.mouse.pointer == v7::PointerDevice::Absolute {
+						latest::PointerDevice::Absolute
+					} else {
+						latest::PointerDevice::Relative
+					},
+					screen_size: old.daemon.mouse.screen_size,
+				},
+				keyboard: latest::KeyboardSettings {
+					added_delay: old.daemon.keyboard.added_delay,
+					disabled: old.daemon.keyboard.disabled,
+				},
+				emergency_stop: latest::EmergencyStopSettings {
+					disabled: old.daemon.emergency_stop.disabled,
+					keys: old.daemon.emergency_stop.keys,
+					presses: old.daemon.emergency_stop.presses,
+					within: old.daemon.emergency_stop.within,
+				},
 			},
 		};
 	}
@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct ClientSettings {
+	pub disable_window_controls: bool,
+	pub notification: bool,
+	pub disable_global_shortcut: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub enum Methods {
+	DBus,
+	UnixSocket,
+}
+
+/// Layout the compositor uses for the virtual keyboard, strings in key sequences are typed with it
+#[derive(Serialize, Deserialize, Clone)]
+pub struct KeyboardLayoutSettings {
+	/// setxkbmap-style name, e.g. `de`, `fr` or `us(dvorak)`
+	pub layout: String,
+	/// Compiled XKB keymap used instead of `layout`, e.g. written by `xkbcli compile-keymap`
+	pub keymap_file: Option<String>,
+}
+
+impl Default for KeyboardLayoutSettings {
+	fn default() -> Self {
+		return Self {
+			layout: "us".into(),
+			keymap_file: None,
+		};
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GeneralSettings {
+	pub communication_method: Methods,
+	pub socket_path: Option<String>,
+	pub keyboard_layout: KeyboardLayoutSettings,
+}
+
+impl Default for GeneralSettings {
+	fn default() -> Self {
+		return Self {
+			socket_path: Some("/run/user/$id/autoclicker.socket".into()),
+			communication_method: Methods::DBus,
+			keyboard_layout: KeyboardLayoutSettings::default(),
+		};
+	}
+}
+
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct KeyboardSettings {
+	pub disabled: bool,
+	pub added_delay: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub enum PointerDevice {
+	/// Moves to a position by moving to the top left corner first, then relative to there
+	Relative,
+	/// Tablet-like device that reports positions directly, mapped to `screen_size`
+	Absolute,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MouseSettings {
+	pub disabled: bool,
+	pub added_delay: u64,
+	pub pointer: PointerDevice,
+	/// Not detected, has to match the size of the whole desktop for the absolute pointer to land on the right spot
+	pub screen_size: (u32, u32),
+}
+
+impl Default for MouseSettings {
+	fn default() -> Self {
+		return Self {
+			disabled: false,
+			added_delay: 0,
+			pointer: PointerDevice::Relative,
+			screen_size: (1920, 1080),
+		};
+	}
+}
+
+/// Key combo on a physical keyboard that makes the daemon stop every job, whatever client started it
+#[derive(Serialize, Deserialize, Clone)]
+pub struct EmergencyStopSettings {
+	pub disabled: bool,
+	/// evdev names of the keys that have to be held together, e.g. `["KEY_LEFTCTRL", "KEY_ESC"]`
+	pub keys: Vec<String>,
+	/// How often the keys have to be pressed
+	pub presses: u32,
+	/// Time in ms all presses have to happen in
+	pub within: u64,
+}
+
+impl Default for EmergencyStopSettings {
+	fn default() -> Self {
+		return Self {
+			disabled: false,
+			keys: vec!["KEY_LEFTCTRL".into(), "KEY_ESC".into()],
+			presses: 3,
+			within: 1000,
+		};
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DaemonSettings {
+	pub hyprland_ipc: bool,
+	pub dry_run: bool,
+
+	pub mouse: MouseSettings,
+	pub keyboard: KeyboardSettings,
+	pub emergency_stop: EmergencyStopSettings,
+}
+
+impl Default for DaemonSettings {
+	fn default() -> Self {
+		return Self {
+			dry_run: false,
+			hyprland_ipc: true,
+			keyboard: KeyboardSettings::default(),
+			mouse: MouseSettings::default(),
+			emergency_stop: EmergencyStopSettings::default(),
+		};
+	}
+}
+
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct Settings {
+	pub general: GeneralSettings,
+	pub client: ClientSettings,
+	pub daemon: DaemonSettings,
+}
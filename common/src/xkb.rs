@@ -0,0 +1,489 @@
+use anyhow::{Context, anyhow};
+use nom::{
+	IResult, Parser,
+	bytes::complete::{is_not, tag},
+	character::complete::{char, multispace0, multispace1},
+	combinator::opt,
+	sequence::{delimited, preceded, terminated},
+};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::actions::Actions;
+use crate::keysyms::keysym_char;
+use crate::settings::latest::KeyboardLayoutSettings;
+
+mod tests;
+
+/// Where layouts given by name are looked up
+#[allow(non_upper_case_globals)]
+const xkb_root: &str = "/usr/share/X11/xkb";
+
+/// How deep `include` statements are followed, so a include cycle can't hang the parser
+#[allow(non_upper_case_globals)]
+const max_includes: usize = 16;
+
+/// Built-in US layout, so typing works without the XKB data of the system
+#[allow(non_upper_case_globals)]
+const us_symbols: &str = r#"
+default xkb_symbols "basic" {
+	key <TLDE> { [ grave, asciitilde ] };
+	key <AE01> { [ 1, exclam ] };
+	key <AE02> { [ 2, at ] };
+	key <AE03> { [ 3, numbersign ] };
+	key <AE04> { [ 4, dollar ] };
+	key <AE05> { [ 5, percent ] };
+	key <AE06> { [ 6, asciicircum ] };
+	key <AE07> { [ 7, ampersand ] };
+	key <AE08> { [ 8, asterisk ] };
+	key <AE09> { [ 9, parenleft ] };
+	key <AE10> { [ 0, parenright ] };
+	key <AE11> { [ minus, underscore ] };
+	key <AE12> { [ equal, plus ] };
+
+	key <AD01> { [ q, Q ] };
+	key <AD02> { [ w, W ] };
+	key <AD03> { [ e, E ] };
+	key <AD04> { [ r, R ] };
+	key <AD05> { [ t, T ] };
+	key <AD06> { [ y, Y ] };
+	key <AD07> { [ u, U ] };
+	key <AD08> { [ i, I ] };
+	key <AD09> { [ o, O ] };
+	key <AD10> { [ p, P ] };
+	key <AD11> { [ bracketleft, braceleft ] };
+	key <AD12> { [ bracketright, braceright ] };
+
+	key <AC01> { [ a, A ] };
+	key <AC02> { [ s, S ] };
+	key <AC03> { [ d, D ] };
+	key <AC04> { [ f, F ] };
+	key <AC05> { [ g, G ] };
+	key <AC06> { [ h, H ] };
+	key <AC07> { [ j, J ] };
+	key <AC08> { [ k, K ] };
+	key <AC09> { [ l, L ] };
+	key <AC10> { [ semicolon, colon ] };
+	key <AC11> { [ apostrophe, quotedbl ] };
+
+	key <AB01> { [ z, Z ] };
+	key <AB02> { [ x, X ] };
+	key <AB03> { [ c, C ] };
+	key <AB04> { [ v, V ] };
+	key <AB05> { [ b, B ] };
+	key <AB06> { [ n, N ] };
+	key <AB07> { [ m, M ] };
+	key <AB08> { [ comma, less ] };
+	key <AB09> { [ period, greater ] };
+	key <AB10> { [ slash, question ] };
+
+	key <BKSL> { [ backslash, bar ] };
+};
+"#;
+
+/// Modifiers held for each shift level, the third level is reached with AltGr
+#[allow(non_upper_case_globals)]
+const levels: [&[&str]; 4] = [&[], &["KEY_LEFTSHIFT"], &["KEY_RIGHTALT"], &["KEY_RIGHTALT", "KEY_LEFTSHIFT"]];
+
+type ParseResult<'a, O> = IResult<&'a str, O>;
+
+/// evdev key of an XKB key name, only the keys that type text
+fn evdev_key(name: &str) -> Option<&'static str> {
+	return Some(match name {
+		"TLDE" => "KEY_GRAVE",
+		"AE01" => "KEY_1",
+		"AE02" => "KEY_2",
+		"AE03" => "KEY_3",
+		"AE04" => "KEY_4",
+		"AE05" => "KEY_5",
+		"AE06" => "KEY_6",
+		"AE07" => "KEY_7",
+		"AE08" => "KEY_8",
+		"AE09" => "KEY_9",
+		"AE10" => "KEY_0",
+		"AE11" => "KEY_MINUS",
+		"AE12" => "KEY_EQUAL",
+		"AE13" => "KEY_YEN",
+		"AD01" => "KEY_Q",
+		"AD02" => "KEY_W",
+		"AD03" => "KEY_E",
+		"AD04" => "KEY_R",
+		"AD05" => "KEY_T",
+		"AD06" => "KEY_Y",
+		"AD07" => "KEY_U",
+		"AD08" => "KEY_I",
+		"AD09" => "KEY_O",
+		"AD10" => "KEY_P",
+		"AD11" => "KEY_LEFTBRACE",
+		"AD12" => "KEY_RIGHTBRACE",
+		"AC01" => "KEY_A",
+		"AC02" => "KEY_S",
+		"AC03" => "KEY_D",
+		"AC04" => "KEY_F",
+		"AC05" => "KEY_G",
+		"AC06" => "KEY_H",
+		"AC07" => "KEY_J",
+		"AC08" => "KEY_K",
+		"AC09" => "KEY_L",
+		"AC10" => "KEY_SEMICOLON",
+		"AC11" => "KEY_APOSTROPHE",
+		"AC12" | "BKSL" => "KEY_BACKSLASH",
+		"AB01" => "KEY_Z",
+		"AB02" => "KEY_X",
+		"AB03" => "KEY_C",
+		"AB04" => "KEY_V",
+		"AB05" => "KEY_B",
+		"AB06" => "KEY_N",
+		"AB07" => "KEY_M",
+		"AB08" => "KEY_COMMA",
+		"AB09" => "KEY_DOT",
+		"AB10" => "KEY_SLASH",
+		"AB11" => "KEY_RO",
+		"LSGT" => "KEY_102ND",
+		"SPCE" => "KEY_SPACE",
+		_ => return None,
+	});
+}
+
+/// Character of a keysym as written in a keymap: a name, `U20AC` or a number
+fn keysym(name: &str) -> Option<char> {
+	if let Some(c) = keysym_char(name) {
+		return Some(c);
+	}
+
+	if let Some(hex) = name.strip_prefix('U').filter(|hex| hex.len() >= 4) {
+		return u32::from_str_radix(hex, 16).ok().and_then(char::from_u32);
+	}
+
+	let value = u32::from_str_radix(name.strip_prefix("0x")?, 16).ok()?;
+	return match value {
+		0x20..=0x7e | 0xa0..=0xff => char::from_u32(value),
+		0x1000100..=0x110ffff => char::from_u32(value - 0x1000000),
+		_ => None,
+	};
+}
+
+fn strip_comments(text: &str) -> String {
+	return text
+		.lines()
+		.map(|line| line.split_once("//").map(|l| l.0).unwrap_or(line))
+		.collect::<Vec<&str>>()
+		.join("\n");
+}
+
+/// Splits `text` at every `separator` that is not inside brackets, braces or quotes
+fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+	let mut parts = Vec::new();
+	let mut depth: i32 = 0;
+	let mut quoted = false;
+	let mut start = 0;
+	for (i, c) in text.char_indices() {
+		match c {
+			'"' => quoted = !quoted,
+			'[' | '{' | '(' if !quoted => depth += 1,
+			']' | '}' | ')' if !quoted => depth -= 1,
+			c if c == separator && !quoted && depth == 0 => {
+				parts.push(&text[start..i]);
+				start = i + c.len_utf8();
+			}
+			_ => {}
+		}
+	}
+	parts.push(&text[start..]);
+
+	return parts;
+}
+
+fn quoted(input: &str) -> ParseResult<'_, &str> {
+	return delimited(char('"'), opt(is_not("\"")), char('"'))
+		.map(|s| s.unwrap_or_default())
+		.parse(input);
+}
+
+/// `include "pc+de(nodeadkeys)"`, the merge keyword is returned with the included names
+fn include(input: &str) -> ParseResult<'_, (&str, &str)> {
+	return preceded(
+		multispace0,
+		(
+			terminated(nom::branch::alt((tag("include"), tag("augment"), tag("override"), tag("replace"))), multispace0),
+			quoted,
+		),
+	)
+	.parse(input);
+}
+
+/// `key <AE01> { [ 1, exclam ] }`, with an optional merge keyword before it
+fn key(input: &str) -> ParseResult<'_, (&str, &str)> {
+	return preceded(
+		(multispace0, opt((nom::branch::alt((tag("override"), tag("replace"), tag("augment"))), multispace1))),
+		(
+			preceded((tag("key"), multispace0), delimited(char('<'), is_not(">"), char('>'))),
+			preceded(multispace0, delimited(char('{'), is_not("}"), char('}'))),
+		),
+	)
+	.parse(input);
+}
+
+/// Symbols of the first group of a key body, like `[ 1, exclam ]` or `type= "..", symbols[Group1]= [ 1, exclam ]`
+fn group1(body: &str) -> Vec<String> {
+	for item in split_top_level(body, ',') {
+		let item = item.trim();
+		let list = if item.starts_with('[') {
+			item
+		} else if item.starts_with("symbols[Group1]") || item.starts_with("symbols[1]") {
+			match item.split_once('=') {
+				Some((_, list)) => list.trim(),
+				None => continue,
+			}
+		} else {
+			continue;
+		};
+
+		return list
+			.trim_start_matches('[')
+			.trim_end_matches(']')
+			.split(',')
+			.map(|s| s.trim().to_string())
+			.collect();
+	}
+
+	return Vec::new();
+}
+
+/// The `xkb_symbols` sections of a symbols or keymap file, by name, and the name of the default one
+fn sections(text: &str) -> (Vec<(String, String)>, Option<String>) {
+	let mut sections: Vec<(String, String)> = Vec::new();
+	let mut default = None;
+
+	let mut rest = text;
+	while let Some(start) = rest.find("xkb_symbols") {
+		let flags = &rest[..start];
+		let flags = flags.rsplit_once(['}', ';']).map(|f| f.1).unwrap_or(flags);
+		rest = &rest[start + "xkb_symbols".len()..];
+
+		let (name, body_start) = match quoted(rest.trim_start()) {
+			Ok((after, name)) => (name.to_string(), after),
+			Err(_) => (String::new(), rest),
+		};
+		let Some(open) = body_start.find('{') else {
+			break;
+		};
+
+		let body = &body_start[open + 1..];
+		let mut depth = 1;
+		let Some(end) = body.char_indices().find_map(|(i, c)| {
+			match c {
+				'{' => depth += 1,
+				'}' => depth -= 1,
+				_ => {}
+			}
+			return Some(i).filter(|_| depth == 0);
+		}) else {
+			break;
+		};
+
+		if flags.split_whitespace().any(|flag| flag == "default") {
+			default = Some(name.clone());
+		}
+		sections.push((name, body[..end].to_string()));
+		rest = &body[end + 1..];
+	}
+
+	return (sections, default);
+}
+
+/// Reads the symbols of a section, following its includes, into `keys`
+fn read_section(text: &str, section: Option<&str>, keys: &mut BTreeMap<String, Vec<String>>, augment: bool, depth: usize) -> anyhow::Result<()> {
+	let text = strip_comments(text);
+	let (sections, default) = sections(&text);
+	let name = section.map(str::to_string).or(default);
+	let body = match &name {
+		Some(name) => sections.iter().find(|s| s.0 == *name),
+		None => sections.first(),
+	};
+	let Some((_, body)) = body else {
+		return Err(anyhow!("no xkb_symbols section {}", name.unwrap_or_default()));
+	};
+
+	let mut rest: &str = body;
+	while !rest.trim().is_empty() {
+		// includes are not ended by a `;`
+		if let Ok((after, (merge, names))) = include(rest) {
+			rest = after;
+			let mut merge = merge;
+			let mut start = 0;
+			for (i, c) in names.char_indices().chain([(names.len(), '+')]) {
+				if c != '+' && c != '|' {
+					continue;
+				}
+
+				let include = &names[start..i];
+				if !include.is_empty() {
+					include_file(include, keys, augment || merge == "augment", depth + 1)?;
+				}
+				merge = if c == '|' {
+					"augment"
+				} else {
+					"override"
+				};
+				start = i + 1;
+			}
+			continue;
+		}
+
+		let mut statements = split_top_level(rest, ';').into_iter();
+		let statement = statements.next().unwrap_or_default();
+		rest = &rest[(statement.len() + 1).min(rest.len())..];
+
+		let Ok((_, (name, body))) = key(statement) else {
+			continue;
+		};
+
+		let symbols = group1(body);
+		if symbols.is_empty() || (augment && keys.contains_key(name)) {
+			continue;
+		}
+		keys.insert(name.to_string(), symbols);
+	}
+
+	return Ok(());
+}
+
+/// Reads an included `file(section)` from the symbols directory
+fn include_file(include: &str, keys: &mut BTreeMap<String, Vec<String>>, augment: bool, depth: usize) -> anyhow::Result<()> {
+	if depth > max_includes {
+		return Err(anyhow!("too many nested includes at {include}"));
+	}
+
+	// only the first group is typed with, `ru:2` puts a layout on the second one
+	let include = match include.split_once(':') {
+		Some((_, group)) if group != "1" => return Ok(()),
+		Some((include, _)) => include,
+		None => include,
+	};
+
+	let (file, section) = match include.split_once('(') {
+		Some((file, section)) => (file, Some(section.trim_end_matches(')'))),
+		None => (include, None),
+	};
+	if file.is_empty() || file.starts_with('/') || file.contains("..") {
+		return Err(anyhow!("invalid symbols name: {include}"));
+	}
+
+	let path = Path::new(xkb_root).join("symbols").join(file);
+	let text = std::fs::read_to_string(&path).with_context(|| format!("could not read {}", path.display()))?;
+	return read_section(&text, section, keys, augment, depth).with_context(|| format!("in {include}"));
+}
+
+/// Which keys type which characters, read from an XKB keymap
+#[derive(Debug, Clone)]
+pub struct Layout {
+	name: String,
+	/// evdev key and shift level of every character, the lowest level is used if a character is on several keys
+	chars: HashMap<char, (&'static str, usize)>,
+}
+
+impl Layout {
+	fn new(name: &str, keys: BTreeMap<String, Vec<String>>) -> Self {
+		let mut chars: HashMap<char, (&'static str, usize)> = HashMap::from([('\n', ("KEY_ENTER", 0)), ('\t', ("KEY_TAB", 0)), (' ', ("KEY_SPACE", 0))]);
+		let mut add = |c: char, key: &'static str, level: usize| {
+			if chars.get(&c).is_none_or(|existing| existing.1 > level) {
+				chars.insert(c, (key, level));
+			}
+		};
+
+		for (name, symbols) in keys.iter() {
+			let Some(key) = evdev_key(name) else {
+				continue;
+			};
+
+			for (level, symbol) in symbols.iter().take(levels.len()).enumerate() {
+				if let Some(c) = keysym(symbol) {
+					add(c, key, level);
+				}
+			}
+
+			// a single lowercase letter is an alphabetic key, shift types the uppercase letter
+			let single = symbols
+				.first()
+				.and_then(|s| keysym(s))
+				.filter(|c| c.is_lowercase() && symbols.len() == 1);
+			if let Some(upper) = single.and_then(|c| c.to_uppercase().next()) {
+				add(upper, key, 1);
+			}
+		}
+
+		return Self { name: name.to_string(), chars };
+	}
+
+	pub fn us() -> Self {
+		return Self::parse("us", us_symbols).expect("the built-in layout is valid");
+	}
+
+	/// Reads the default `xkb_symbols` section of a symbols file, or the one of a keymap like `xkbcli compile-keymap` writes
+	pub fn parse(name: &str, text: &str) -> anyhow::Result<Self> {
+		let mut keys = BTreeMap::new();
+		read_section(text, None, &mut keys, false, 0)?;
+		return Ok(Self::new(name, keys));
+	}
+
+	/// Loads a layout by its setxkbmap-style name, like `de`, `fr(azerty)` or `us(dvorak)`
+	pub fn from_name(name: &str) -> anyhow::Result<Self> {
+		let mut keys = BTreeMap::new();
+		include_file(name, &mut keys, false, 0).with_context(|| format!("could not load keyboard layout {name}"))?;
+		if !keys.keys().any(|key| evdev_key(key).is_some()) {
+			return Err(anyhow!("keyboard layout {name} has no keys that type text"));
+		}
+
+		return Ok(Self::new(name, keys));
+	}
+
+	pub fn from_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+		let path = path.as_ref();
+		let text = std::fs::read_to_string(path).with_context(|| format!("could not read {}", path.display()))?;
+		return Self::parse(&path.display().to_string(), &text).with_context(|| format!("could not load keymap {}", path.display()));
+	}
+
+	/// The layout configured in the settings
+	pub fn load(settings: &KeyboardLayoutSettings) -> anyhow::Result<Self> {
+		if let Some(path) = &settings.keymap_file {
+			return Self::from_file(path);
+		}
+
+		if settings.layout == "us" {
+			return Ok(Self::us());
+		}
+
+		return Self::from_name(&settings.layout);
+	}
+
+	pub fn name(&self) -> &str {
+		return &self.name;
+	}
+
+	/// Actions that type `text`, holding shift or AltGr where a character needs it
+	pub fn type_str(&self, text: &str) -> anyhow::Result<Vec<Actions>> {
+		let mut actions = Vec::new();
+		for c in text.chars() {
+			let Some(&(key, level)) = self.chars.get(&c) else {
+				return Err(anyhow!("{c:?} can't be typed with the {} keyboard layout", self.name));
+			};
+
+			if level == 0 {
+				actions.push(Actions::PressAndRelease(key.into()));
+				continue;
+			}
+
+			for modifier in levels[level] {
+				actions.push(Actions::Press(modifier.to_string()));
+			}
+			actions.push(Actions::Press(key.into()));
+			for modifier in levels[level].iter().rev() {
+				actions.push(Actions::Release(modifier.to_string()));
+			}
+			actions.push(Actions::Release(key.into()));
+		}
+
+		return Ok(actions);
+	}
+}
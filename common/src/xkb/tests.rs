@@ -0,0 +1,89 @@
+#[cfg(test)]
+use super::*;
+
+#[cfg(test)]
+const german: &str = r#"
+// trimmed down de(basic)
+default partial alphanumeric_keys
+xkb_symbols "basic" {
+	name[Group1]="German";
+
+	key <AE02>	{ [         2,   quotedbl,  twosuperior,    oneeighth ]	};
+	key <AE11> {type[Group1]="FOUR_LEVEL_PLUS_LOCK",  symbols[Group1]=
+	              [ssharp, question, backslash, questiondown, 0x1001E9E ]};
+	key <AD03>	{ [         e,          E,     EuroSign,     EuroSign ]	};
+	key <AD06>	{ [         z,          Z,    leftarrow,          yen ]	};
+	key <AC10>	{ [odiaeresis, Odiaeresis, dead_doubleacute, dead_belowdot ] };
+	key <AB01>	{ [         y,          Y,       guillemotright,    U203A 	] };
+	key <AD01>	{ [ q ] };
+};
+
+xkb_symbols "other" {
+	key <AD06>	{ [ a, A ] };
+};
+"#;
+
+#[test]
+fn test_layout_parsing() {
+	let layout = Layout::parse("de", german).unwrap();
+	assert_eq!(layout.type_str("z").unwrap(), vec![Actions::PressAndRelease("KEY_Y".into())]);
+	assert_eq!(layout.type_str("ö").unwrap(), vec![Actions::PressAndRelease("KEY_SEMICOLON".into())]);
+	assert_eq!(
+		layout.type_str("?").unwrap(),
+		vec![
+			Actions::Press("KEY_LEFTSHIFT".into()),
+			Actions::Press("KEY_MINUS".into()),
+			Actions::Release("KEY_LEFTSHIFT".into()),
+			Actions::Release("KEY_MINUS".into()),
+		]
+	);
+	assert_eq!(
+		layout.type_str("€").unwrap(),
+		vec![
+			Actions::Press("KEY_RIGHTALT".into()),
+			Actions::Press("KEY_E".into()),
+			Actions::Release("KEY_RIGHTALT".into()),
+			Actions::Release("KEY_E".into()),
+		]
+	);
+	assert_eq!(layout.type_str("›").unwrap().len(), 6);
+
+	// alphabetic keys with a single symbol type the uppercase letter with shift
+	assert_eq!(layout.type_str("Q").unwrap()[0], Actions::Press("KEY_LEFTSHIFT".into()));
+	assert_eq!(layout.type_str(" \n").unwrap().len(), 2);
+
+	let err = layout.type_str("a").unwrap_err();
+	assert!(
+		err.to_string()
+			.contains("can't be typed with the de keyboard layout")
+	);
+}
+
+#[test]
+fn test_keysyms() {
+	assert_eq!(keysym("exclam"), Some('!'));
+	assert_eq!(keysym("U20AC"), Some('€'));
+	assert_eq!(keysym("0x1001E9E"), Some('ẞ'));
+	assert_eq!(keysym("0x00e9"), Some('é'));
+	assert_eq!(keysym("dead_acute"), None);
+	assert_eq!(keysym("NoSymbol"), None);
+}
+
+#[test]
+fn test_us_layout() {
+	let layout = Layout::us();
+	assert_eq!(
+		layout.type_str("A@").unwrap(),
+		vec![
+			Actions::Press("KEY_LEFTSHIFT".into()),
+			Actions::Press("KEY_A".into()),
+			Actions::Release("KEY_LEFTSHIFT".into()),
+			Actions::Release("KEY_A".into()),
+			Actions::Press("KEY_LEFTSHIFT".into()),
+			Actions::Press("KEY_2".into()),
+			Actions::Release("KEY_LEFTSHIFT".into()),
+			Actions::Release("KEY_2".into()),
+		]
+	);
+	assert!(layout.type_str("é").is_err());
+}